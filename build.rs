@@ -0,0 +1,57 @@
+use std::{fs, path::Path, process::Command, time::{SystemTime, UNIX_EPOCH}};
+
+fn main() {
+    check_font();
+    emit_build_info();
+}
+
+// 启用 bundled-font 时图片渲染通过 include_bytes! 内置 fonts/font.ttf，字体缺失时先给出明确的提示
+fn check_font() {
+    println!("cargo:rerun-if-changed=fonts/font.ttf");
+    if std::env::var_os("CARGO_FEATURE_BUNDLED_FONT").is_some() && !Path::new("fonts/font.ttf").exists() {
+        println!("cargo:warning=Font not found at fonts/font.ttf");
+        println!("cargo:warning=请把一个支持中文的 TrueType 字体放到 fonts/font.ttf 后重新编译，");
+        println!("cargo:warning=或者用 --no-default-features --features serde 编译，并在 bingo.toml 中设置 font_path");
+    }
+}
+
+// --version 使用的构建日期（UTC）与 git 提交哈希
+fn emit_build_info() {
+    // 设置了 SOURCE_DATE_EPOCH 时使用它，便于可复现构建
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    let secs = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0));
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    println!("cargo:rustc-env=BUILD_DATE={:04}-{:02}-{:02}", year, month, day);
+
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=BUILD_COMMIT={}", commit);
+
+    // 切换分支或提交后重新生成
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    if let Some(reference) = fs::read_to_string(".git/HEAD").ok().and_then(|head| head.strip_prefix("ref: ").map(|r| r.trim().to_string())) {
+        println!("cargo:rerun-if-changed=.git/{}", reference);
+    }
+}
+
+// 自 1970-01-01 起的天数转换为公历日期（Howard Hinnant 的 civil_from_days 算法）
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,56 @@
+// 用历史题目对比各求解策略的解数量与耗时：
+// cargo run --release --example strategy_comparison -- 2025-12-08 2026-03-09
+use std::time::Instant;
+
+use bingo::solver::SolverStrategy;
+use bingo::storage::load_solutions_json;
+use bingo::{Color, Solver};
+
+fn main() {
+    let dates: Vec<String> = std::env::args().skip(1).collect();
+    if dates.is_empty() {
+        eprintln!("用法: strategy_comparison <YYYY-MM-DD>...");
+        return;
+    }
+
+    // 中文字符在终端中占两列，表头按显示宽度手动对齐
+    println!("{:<10} {:<22} {:>4} {:>10} {:>10}", "日期", "策略", "解数", "节点数", "耗时");
+    for date in &dates {
+        let solutions = match load_solutions_json(&format!("data/{}/solutions.json", date)) {
+            Ok(solutions) if !solutions.is_empty() => solutions,
+            Ok(_) => continue,
+            Err(e) => {
+                eprintln!("{}: {}", date, e);
+                continue;
+            }
+        };
+        let color_grid: Vec<Vec<Color>> = solutions[0]
+            .0
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.color).collect())
+            .collect();
+
+        // 回溯类策略额外测一次 solve_parallel
+        let runs = SolverStrategy::ALL.into_iter().flat_map(|strategy| {
+            let parallel = matches!(strategy, SolverStrategy::Backtrack | SolverStrategy::BacktrackFc);
+            [(strategy, false), (strategy, true)].into_iter().take(if parallel { 2 } else { 1 })
+        });
+        for (strategy, parallel) in runs {
+            let solver = Solver::builder(color_grid.clone()).max_checked(25).strategy(strategy).build().unwrap();
+            let start = Instant::now();
+            let found = if parallel { solver.solve_parallel() } else { solver.solve() };
+            let name = if parallel { format!("{} (parallel)", strategy.name()) } else { strategy.name().to_string() };
+            let elapsed = start.elapsed();
+            // 并行求解在各任务的克隆上计数，没有汇总
+            let nodes = if parallel { "-".to_string() } else { solver.nodes_visited().to_string() };
+            println!(
+                "{:<12} {:<24} {:>6} {:>13} {:>12.1?}",
+                date,
+                name,
+                format!("{}/{}", found.len(), solutions.len()),
+                nodes,
+                elapsed
+            );
+        }
+    }
+}
@@ -0,0 +1,360 @@
+use chrono::NaiveDate;
+use flate2::read::GzDecoder;
+use log::info;
+#[cfg(feature = "serde")]
+use log::warn;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{fs, io::{self, Read}, path::{Path, PathBuf}};
+
+use crate::difficulty::DifficultyTier;
+#[cfg(feature = "serde")]
+use crate::difficulty::{classify_difficulty, solutions_difficulty};
+use crate::error::BingoError;
+use crate::grid::Color;
+#[cfg(feature = "serde")]
+use crate::{grid::Grid, storage::parse_solutions_json};
+
+const TARBALL_SUFFIX: &str = ".tar.gz";
+
+// ----------------------------- 历史题目归档 -----------------------------
+// data/ 目录下按日期（YYYY-MM-DD）存放的历史题目
+pub struct PuzzleArchive {
+    root: PathBuf,
+}
+
+// 历史题目检索条件，均为 None 时返回全部日期；日期范围不含端点
+#[derive(Default)]
+pub struct ArchiveQuery {
+    pub min_solutions: Option<usize>,
+    pub max_solutions: Option<usize>,
+    pub difficulty: Option<DifficultyTier>,
+    pub date_after: Option<NaiveDate>,
+    pub date_before: Option<NaiveDate>,
+    pub has_color: Option<Color>,
+}
+
+// data/index.json 中每天一条的摘要。difficulty_tier 与 solutions.json 中的同名字段相同；
+// 旧版索引只有按勾选数划分的 difficulty，缺少该字段而读取失败，会被整体重建
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub date: String,
+    pub total_solutions: usize,
+    pub difficulty_tier: DifficultyTier,
+    pub colors: Vec<Color>,
+}
+
+#[cfg(feature = "serde")]
+impl IndexEntry {
+    pub fn new(date: &str, solutions: &[Grid]) -> Self {
+        Self {
+            date: date.to_string(),
+            total_solutions: solutions.len(),
+            difficulty_tier: classify_difficulty(solutions_difficulty(solutions)),
+            colors: solutions.first().map(|grid| grid.colors_present()).unwrap_or_default(),
+        }
+    }
+}
+
+impl ArchiveQuery {
+    #[cfg(feature = "serde")]
+    fn matches(&self, entry: &IndexEntry) -> bool {
+        let date = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d").ok();
+        self.min_solutions.is_none_or(|min| entry.total_solutions >= min)
+            && self.max_solutions.is_none_or(|max| entry.total_solutions <= max)
+            && self.difficulty.is_none_or(|tier| entry.difficulty_tier == tier)
+            && self.date_after.is_none_or(|after| date.is_some_and(|date| date > after))
+            && self.date_before.is_none_or(|before| date.is_some_and(|date| date < before))
+            && self.has_color.is_none_or(|color| entry.colors.contains(&color))
+    }
+}
+
+pub struct ArchiveDiskUsage {
+    pub total_bytes: u64,
+    pub per_date: Vec<(String, u64)>, // 按占用从大到小排序
+}
+
+impl PuzzleArchive {
+    pub fn open(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    // 所有日期（日期文件夹或 <date>.tar.gz 压缩包），按日期升序
+    pub fn dates(&self) -> Result<Vec<String>, BingoError> {
+        let mut dates = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let date = if entry.path().is_dir() {
+                name
+            } else if let Some(date) = name.strip_suffix(TARBALL_SUFFIX) {
+                date.to_string()
+            } else {
+                continue;
+            };
+            if NaiveDate::parse_from_str(&date, "%Y-%m-%d").is_ok() {
+                dates.push(date);
+            }
+        }
+        dates.sort();
+        dates.dedup(); // 同一天既有文件夹又有压缩包时只算一次
+        Ok(dates)
+    }
+
+    // 读取某一天的某个文件，优先从日期文件夹读取，不存在时再查找压缩包
+    pub fn read_file(&self, date: &str, name: &str) -> Result<Vec<u8>, BingoError> {
+        let dir = self.root.join(date);
+        if dir.is_dir() {
+            return Ok(fs::read(dir.join(name))?);
+        }
+        let tarball = self.root.join(format!("{}{}", date, TARBALL_SUFFIX));
+        let mut archive = tar::Archive::new(GzDecoder::new(fs::File::open(&tarball)?));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if entry.path()?.as_ref() == Path::new(name) {
+                let mut bytes = Vec::new();
+                entry.read_to_end(&mut bytes)?;
+                return Ok(bytes);
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} 中没有 {}", tarball.display(), name),
+        )
+        .into())
+    }
+
+    // 删除除最近 keep_last_n 天以外的所有日期，返回删除的天数
+    pub fn purge_old(&mut self, keep_last_n: usize) -> Result<usize, BingoError> {
+        let dates = self.dates()?;
+        let purge_count = dates.len().saturating_sub(keep_last_n);
+        for date in &dates[..purge_count] {
+            self.remove_date(date)?;
+        }
+        Ok(purge_count)
+    }
+
+    // 删除早于 before（YYYY-MM-DD，不含当天）的所有日期，返回删除的天数
+    pub fn purge_before(&mut self, before: &str) -> Result<usize, BingoError> {
+        // 日期格式固定，字符串比较即为日期先后
+        let old: Vec<String> = self.dates()?.into_iter().filter(|date| date.as_str() < before).collect();
+        for date in &old {
+            self.remove_date(date)?;
+        }
+        Ok(old.len())
+    }
+
+    fn remove_date(&self, date: &str) -> Result<(), BingoError> {
+        let dir = self.root.join(date);
+        if dir.is_dir() {
+            fs::remove_dir_all(&dir)?;
+            info!("🗑️ 已删除 {}", dir.display());
+        }
+        let tarball = self.root.join(format!("{}{}", date, TARBALL_SUFFIX));
+        if tarball.is_file() {
+            fs::remove_file(&tarball)?;
+            info!("🗑️ 已删除 {}", tarball.display());
+        }
+        Ok(())
+    }
+
+    // 读取某一天的全部解；与 storage::solutions_json_file 一样先找 solutions.json.gz，再找 solutions.json
+    #[cfg(feature = "serde")]
+    pub fn load_solutions(&self, date: &str) -> Result<Vec<Grid>, BingoError> {
+        let text = match self.read_file(date, "solutions.json.gz") {
+            Ok(bytes) => {
+                let mut text = String::new();
+                GzDecoder::new(bytes.as_slice()).read_to_string(&mut text)?;
+                text
+            }
+            Err(_) => String::from_utf8_lossy(&self.read_file(date, "solutions.json")?).into_owned(),
+        };
+        parse_solutions_json(&text)
+    }
+
+    #[cfg(feature = "serde")]
+    fn index_path(&self) -> PathBuf {
+        self.root.join("index.json")
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn load_index(&self) -> Result<Vec<IndexEntry>, BingoError> {
+        Ok(serde_json::from_str(&fs::read_to_string(self.index_path())?)?)
+    }
+
+    #[cfg(feature = "serde")]
+    fn save_index(&self, entries: &[IndexEntry]) -> Result<(), BingoError> {
+        fs::write(self.index_path(), serde_json::to_string_pretty(entries)?)?;
+        Ok(())
+    }
+
+    // 重新扫描所有日期生成 data/index.json，读取失败的日期跳过
+    #[cfg(feature = "serde")]
+    pub fn rebuild_index(&self) -> Result<Vec<IndexEntry>, BingoError> {
+        let mut entries = Vec::new();
+        for date in self.dates()? {
+            match self.load_solutions(&date) {
+                Ok(solutions) => entries.push(IndexEntry::new(&date, &solutions)),
+                Err(e) => warn!("⚠️ 跳过 {}: {}", date, e),
+            }
+        }
+        self.save_index(&entries)?;
+        info!("已重建索引，共 {} 天", entries.len());
+        Ok(entries)
+    }
+
+    // 写入或替换某一天的索引条目；索引不存在时先完整重建
+    #[cfg(feature = "serde")]
+    pub fn update_index(&self, date: &str, solutions: &[Grid]) -> Result<(), BingoError> {
+        let mut entries = match self.load_index() {
+            Ok(entries) => entries,
+            Err(_) => return self.rebuild_index().map(|_| ()),
+        };
+        entries.retain(|entry| entry.date != date);
+        entries.push(IndexEntry::new(date, solutions));
+        entries.sort_by(|a, b| a.date.cmp(&b.date));
+        self.save_index(&entries)
+    }
+
+    // 按条件检索历史题目，返回符合条件的日期（升序）；已被清理的日期不会返回
+    #[cfg(feature = "serde")]
+    pub fn search(&self, query: ArchiveQuery) -> Vec<String> {
+        let entries = match self.load_index().or_else(|_| self.rebuild_index()) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("⚠️ 无法读取索引: {}", e);
+                return Vec::new();
+            }
+        };
+        let existing = self.dates().unwrap_or_default();
+        entries
+            .into_iter()
+            .filter(|entry| existing.contains(&entry.date) && query.matches(entry))
+            .map(|entry| entry.date)
+            .collect()
+    }
+
+    // 某一天占用的字节数（文件夹与压缩包之和）
+    fn date_size(&self, date: &str) -> u64 {
+        let tarball = self.root.join(format!("{}{}", date, TARBALL_SUFFIX));
+        dir_size(&self.root.join(date)) + fs::metadata(tarball).map(|m| m.len()).unwrap_or(0)
+    }
+
+    pub fn disk_usage(&self) -> ArchiveDiskUsage {
+        let total_bytes = dir_size(&self.root);
+        let mut per_date: Vec<(String, u64)> = self
+            .dates()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|date| {
+                let size = self.date_size(&date);
+                (date, size)
+            })
+            .collect();
+        per_date.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ArchiveDiskUsage { total_bytes, per_date }
+    }
+}
+
+impl ArchiveDiskUsage {
+    // 占用超过中位数 10 倍的日期，通常意味着当天生成了数百张解图片
+    pub fn outliers(&self) -> Vec<&(String, u64)> {
+        let mut sizes: Vec<u64> = self.per_date.iter().map(|(_, size)| *size).collect();
+        if sizes.is_empty() {
+            return Vec::new();
+        }
+        sizes.sort_unstable();
+        let median = sizes[sizes.len() / 2];
+        self.per_date.iter().filter(|(_, size)| *size > median * 10).collect()
+    }
+
+    // 人类可读的表格
+    pub fn report(&self) -> String {
+        let outliers = self.outliers();
+        let mut lines = vec![
+            // 中文字符在终端中占两列，表头按显示宽度手动对齐
+            format!("{:<10} {:>8}", "日期", "占用"),
+            "-".repeat(24),
+        ];
+        for entry in &self.per_date {
+            let mark = if outliers.contains(&entry) { "  ⚠️" } else { "" };
+            lines.push(format!("{:<12} {:>10}{}", entry.0, format_size(entry.1), mark));
+        }
+        lines.push("-".repeat(24));
+        lines.push(format!("{:<10} {:>10}", "总计", format_size(self.total_bytes)));
+        lines.join("\n")
+    }
+}
+
+// ----------------------------- 题目记录 -----------------------------
+// data/archive.json 中的一条记录：每次成功生成追加一条，同一天重新生成时会有多条，按追加顺序保留
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PuzzleRecord {
+    pub date: String,
+    pub color_grid: Vec<Vec<Color>>,
+    pub solution_count: usize,
+    pub difficulty: f64,
+    pub seed: Option<u64>,
+}
+
+// 读出已有记录后追加一条，先写入同目录下的临时文件再重命名，写到一半中断也不会损坏原文件。
+// 文件不存在时新建；已有内容无法解析时返回错误，不覆盖
+#[cfg(feature = "serde")]
+pub fn append_to_archive(record: &PuzzleRecord, path: &str) -> Result<(), BingoError> {
+    let mut records = read_archive(path)?;
+    records.push(record.clone());
+    let tmp = format!("{}.tmp", path);
+    fs::write(&tmp, serde_json::to_string_pretty(&records)?)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+// 读取失败（包括文件不存在）时返回空列表
+#[cfg(feature = "serde")]
+pub fn load_archive(path: &str) -> Vec<PuzzleRecord> {
+    read_archive(path).unwrap_or_else(|e| {
+        warn!("⚠️ 无法读取 {}: {}", path, e);
+        Vec::new()
+    })
+}
+
+#[cfg(feature = "serde")]
+fn read_archive(path: &str) -> Result<Vec<PuzzleRecord>, BingoError> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(serde_json::from_str(&text)?),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn format_size(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.2} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+// 递归统计目录下所有文件的大小，读取失败的条目忽略
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                dir_size(&path)
+            } else {
+                fs::metadata(&path).map(|m| m.len()).unwrap_or(0)
+            }
+        })
+        .sum()
+}
@@ -0,0 +1,188 @@
+use crate::config::{MAX_CELLS, MAX_GRID_SIZE};
+use crate::grid::{diag1_index, diag2_index, Color, Grid, DIAGONALS};
+
+// ----------------------------- 位棋盘 -----------------------------
+// 第 i * 8 + j 位表示 (i, j) 是否勾选。回溯时只改一个 u64，规则校验全部是位运算。
+// 不论网格实际边长是多少都按 8 × 8 排布，网格之外的位始终为 0，邻居、行列、对角线的掩码因此与尺寸无关
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BitGrid(pub u64);
+
+const fn bit(i: usize, j: usize) -> u64 {
+    1 << (i * MAX_GRID_SIZE + j)
+}
+
+// 周围一圈八个格子（不含自身）
+pub const NEIGHBOR_MASKS: [u64; MAX_CELLS] = neighbor_masks(false);
+// 上下左右四个格子
+pub const FOUR_NEIGHBOR_MASKS: [u64; MAX_CELLS] = neighbor_masks(true);
+
+const fn neighbor_masks(four_only: bool) -> [u64; MAX_CELLS] {
+    let n = MAX_GRID_SIZE as i32;
+    let mut masks = [0u64; MAX_CELLS];
+    let mut x = 0;
+    while x < MAX_GRID_SIZE {
+        let mut y = 0;
+        while y < MAX_GRID_SIZE {
+            let mut mask = 0;
+            let mut dx = -1i32;
+            while dx <= 1 {
+                let mut dy = -1i32;
+                while dy <= 1 {
+                    let (i, j) = (x as i32 + dx, y as i32 + dy);
+                    let skip = (dx == 0 && dy == 0) || (four_only && dx != 0 && dy != 0);
+                    if !skip && i >= 0 && i < n && j >= 0 && j < n {
+                        mask |= bit(i as usize, j as usize);
+                    }
+                    dy += 1;
+                }
+                dx += 1;
+            }
+            masks[x * MAX_GRID_SIZE + y] = mask;
+            y += 1;
+        }
+        x += 1;
+    }
+    masks
+}
+
+pub const ROW_MASKS: [u64; MAX_GRID_SIZE] = line_masks_of(false);
+pub const COL_MASKS: [u64; MAX_GRID_SIZE] = line_masks_of(true);
+
+const fn line_masks_of(columns: bool) -> [u64; MAX_GRID_SIZE] {
+    let mut masks = [0u64; MAX_GRID_SIZE];
+    let mut k = 0;
+    while k < MAX_GRID_SIZE {
+        let mut other = 0;
+        while other < MAX_GRID_SIZE {
+            masks[k] |= if columns { bit(other, k) } else { bit(k, other) };
+            other += 1;
+        }
+        k += 1;
+    }
+    masks
+}
+
+// 按 diag1_index / diag2_index 编号的对角线
+pub const DIAG1_MASKS: [u64; DIAGONALS] = diagonal_masks(false);
+pub const DIAG2_MASKS: [u64; DIAGONALS] = diagonal_masks(true);
+
+const fn diagonal_masks(anti: bool) -> [u64; DIAGONALS] {
+    let mut masks = [0u64; DIAGONALS];
+    let mut x = 0;
+    while x < MAX_GRID_SIZE {
+        let mut y = 0;
+        while y < MAX_GRID_SIZE {
+            let index = if anti { diag2_index(x, y) } else { diag1_index(x, y) };
+            masks[index] |= bit(x, y);
+            y += 1;
+        }
+        x += 1;
+    }
+    masks
+}
+
+// size × size 网格内所有格子
+pub fn board_mask(size: usize) -> u64 {
+    (0..size).fold(0, |mask, i| (0..size).fold(mask, |mask, j| mask | bit(i, j)))
+}
+
+// 边长为 size 时网格的四个角
+pub fn corner_mask(size: usize) -> u64 {
+    let last = size - 1;
+    bit(0, 0) | bit(0, last) | bit(last, 0) | bit(last, last)
+}
+
+// 边长为 size 时所有可能的五连，顺序与 Grid::iter_lines 一致
+pub fn line_masks(size: usize) -> Vec<u64> {
+    let board = board_mask(size);
+    let mut lines: Vec<u64> = ROW_MASKS[..size].iter().chain(&COL_MASKS[..size]).map(|mask| mask & board).collect();
+    lines.push((0..size).fold(0, |mask, k| mask | bit(k, k)));
+    lines.push((0..size).fold(0, |mask, k| mask | bit(k, size - 1 - k)));
+    lines
+}
+
+impl BitGrid {
+    pub fn from_grid(grid: &Grid) -> Self {
+        let bits = grid.0
+            .iter()
+            .flatten()
+            .filter(|cell| cell.checked)
+            .fold(0, |bits, cell| bits | bit(cell.x, cell.y));
+        BitGrid(bits)
+    }
+
+    pub fn to_grid(self, color_grid: &[Vec<Color>]) -> Grid {
+        let mut grid = Grid::new_blank(color_grid);
+        for cell in grid.0.iter_mut().flatten() {
+            cell.checked = self.is_checked(cell.x, cell.y);
+        }
+        grid
+    }
+
+    #[inline]
+    pub fn is_checked(self, x: usize, y: usize) -> bool {
+        self.0 & bit(x, y) != 0
+    }
+
+    #[inline]
+    pub fn set(&mut self, x: usize, y: usize, checked: bool) {
+        if checked {
+            self.0 |= bit(x, y);
+        } else {
+            self.0 &= !bit(x, y);
+        }
+    }
+
+    #[inline]
+    pub fn checked_count(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    #[inline]
+    fn count(self, mask: u64) -> u32 {
+        (self.0 & mask).count_ones()
+    }
+
+    // 与 Grid 上对应的 check_*_rule 判定结果一致，size 为网格边长（灰格、藏格规则要用到一行、一列的格子数，栗格规则要用到四个角的位置）
+    pub fn check_cell_rule(self, color: Color, x: usize, y: usize, size: usize) -> bool {
+        let i = x * MAX_GRID_SIZE + y;
+        match color {
+            Color::Red => self.0 & NEIGHBOR_MASKS[i] != 0,
+            Color::Blue => self.count(NEIGHBOR_MASKS[i]) <= 2,
+            Color::Green => self.count(ROW_MASKS[x]) == self.count(COL_MASKS[y]),
+            Color::Yellow => {
+                self.count(DIAG1_MASKS[diag1_index(x, y)]) == self.count(DIAG2_MASKS[diag2_index(x, y)])
+            }
+            Color::Purple => self.count(NEIGHBOR_MASKS[i]) % 2 == 1,
+            Color::Orange => self.count(NEIGHBOR_MASKS[i]).is_multiple_of(2),
+            Color::Cyan => !self.is_checked(x, y) || self.0 & FOUR_NEIGHBOR_MASKS[i] != 0,
+            Color::Teal => self.count(DIAG1_MASKS[diag1_index(x, y)]).is_multiple_of(2),
+            Color::Pink => self.0 & NEIGHBOR_MASKS[i] == 0,
+            Color::Gray => self.count(ROW_MASKS[x]) as usize * 2 > size,
+            Color::Maroon => !self.is_checked(x, y) || self.0 & corner_mask(size) != 0,
+            Color::Navy => self.count(COL_MASKS[y]) as usize * 2 > size,
+            _ => true,
+        }
+    }
+
+    // check_cell_rule 读取的全部格子（可能含网格外的位，调用方按需与 board_mask 相与）
+    pub fn rule_mask(color: Color, x: usize, y: usize, size: usize) -> u64 {
+        let i = x * MAX_GRID_SIZE + y;
+        match color {
+            Color::Red | Color::Blue | Color::Purple | Color::Orange | Color::Pink => NEIGHBOR_MASKS[i],
+            Color::Green => ROW_MASKS[x] | COL_MASKS[y],
+            Color::Yellow => DIAG1_MASKS[diag1_index(x, y)] | DIAG2_MASKS[diag2_index(x, y)],
+            Color::Cyan => FOUR_NEIGHBOR_MASKS[i] | bit(x, y),
+            Color::Teal => DIAG1_MASKS[diag1_index(x, y)],
+            Color::Gray => ROW_MASKS[x],
+            Color::Maroon => corner_mask(size) | bit(x, y),
+            Color::Navy => COL_MASKS[y],
+            _ => 0,
+        }
+    }
+
+    // lines 为 line_masks 的结果
+    pub fn has_full_line(self, lines: &[u64]) -> bool {
+        lines.iter().any(|&mask| mask & !self.0 == 0)
+    }
+}
@@ -0,0 +1,345 @@
+use log::LevelFilter;
+#[cfg(feature = "serde")]
+use serde::Deserialize;
+#[cfg(feature = "serde")]
+use std::{fs, io};
+
+use crate::error::BingoError;
+
+// ----------------------------- 运行配置 -----------------------------
+// 可由工作目录下的 bingo.toml 覆盖（见 Config::load），文件中没有写的项保持默认值。
+// 规则文字的行高由字体的纵向度量算出，不单独配置
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize), serde(default, deny_unknown_fields))]
+pub struct Config {
+    pub log_level: String, // 日志级别（未设置 RUST_LOG 时生效）
+    pub compress_json: bool, // 是否以 gzip 压缩写入 solutions.json
+    pub output_dir: String, // 题目与历史归档的根目录
+    pub max_checked: Option<usize>, // 命令行未指定 --max-checked 时的总勾选数上限，不设置时按颜色布局估算
+    pub min_white: usize, // 随机布局至少包含的白格数
+    pub max_black: usize, // 随机布局至多包含的黑格数
+    pub font_path: Option<String>, // 运行时读取的字体文件，不设置时使用编译进程序的 fonts/font.ttf（须启用 bundled-font）
+    pub cell_size: u32, // 以下四项对应 RenderConfig 的同名字段
+    pub rule_font_size: f32,
+    pub margin: u32,
+    pub rule_column_width: u32,
+}
+
+pub const DEFAULT_MIN_WHITE: usize = 2;
+// 黑格必须勾选，相当于白送的勾：黑格越多，离五连只差一两格的行列越多，题目越容易、解也越多，
+// 三四个黑格落在同一行列时几乎一眼就能看出答案。与 ColorConstraints 的默认上限一致
+pub const DEFAULT_MAX_BLACK: usize = 2;
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            log_level: "info".to_string(),
+            compress_json: false,
+            output_dir: "data".to_string(),
+            max_checked: None,
+            min_white: DEFAULT_MIN_WHITE,
+            max_black: DEFAULT_MAX_BLACK,
+            font_path: None,
+            cell_size: 90,
+            rule_font_size: 13.5,
+            margin: 12,
+            rule_column_width: 310,
+        }
+    }
+}
+
+pub const CONFIG_FILE: &str = "bingo.toml";
+
+impl Config {
+    // 无法解析的级别按 info 处理
+    pub fn log_level_filter(&self) -> LevelFilter {
+        self.log_level.parse().unwrap_or(LevelFilter::Info)
+    }
+
+    // 读取 TOML 配置文件，键与字段同名，例如：
+    //   output_dir = "out"
+    //   cell_size = 60
+    // 文件不存在时返回默认配置；内容无法解析或有未知的键时返回错误
+    #[cfg(feature = "serde")]
+    pub fn load(path: &str) -> Result<Self, BingoError> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut root = serde_json::Map::new();
+        for (table, key, value) in parse_toml(&text).map_err(BingoError::Format)? {
+            let value = match value {
+                TomlValue::String(s) => serde_json::Value::String(s),
+                TomlValue::Integer(i) => i.into(),
+                TomlValue::Float(f) => serde_json::Number::from_f64(f)
+                    .map(serde_json::Value::Number)
+                    .ok_or_else(|| BingoError::Format(format!("{} 不是有限的数", key)))?,
+                TomlValue::Boolean(b) => b.into(),
+            };
+            if table.is_empty() {
+                root.insert(key, value);
+            } else {
+                let entry = root.entry(table).or_insert_with(|| serde_json::Value::Object(Default::default()));
+                if let serde_json::Value::Object(map) = entry {
+                    map.insert(key, value);
+                }
+            }
+        }
+        serde_json::from_value(serde_json::Value::Object(root)).map_err(|e| BingoError::Format(e.to_string()))
+    }
+}
+
+// ----------------------------- 网格尺寸 -----------------------------
+// 位棋盘用一个 u64 按 8 × 8 排布，因此边长最大为 8
+pub const MAX_GRID_SIZE: usize = 8;
+pub const MAX_CELLS: usize = MAX_GRID_SIZE * MAX_GRID_SIZE;
+
+// size × size 的方形网格，默认即原来的 5 × 5；五连指一整行、一整列或一条完整的对角线
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridConfig {
+    pub size: usize,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        Self { size: 5 }
+    }
+}
+
+impl GridConfig {
+    // 边长须在 1..=MAX_GRID_SIZE 之间
+    pub fn new(size: usize) -> Result<Self, BingoError> {
+        if !(1..=MAX_GRID_SIZE).contains(&size) {
+            return Err(BingoError::InvalidInput(format!("网格边长须在 1..={} 之间，实际为 {}", MAX_GRID_SIZE, size)));
+        }
+        Ok(Self { size })
+    }
+
+    // 按颜色网格的行数确定尺寸，网格须为方形
+    pub fn of<T>(grid: &[Vec<T>]) -> Result<Self, BingoError> {
+        let config = Self::new(grid.len())?;
+        if let Some((row, cells)) = grid.iter().enumerate().find(|(_, cells)| cells.len() != config.size) {
+            return Err(BingoError::InvalidInput(format!(
+                "网格须为方形：共 {} 行，第 {} 行却有 {} 个格子",
+                config.size,
+                row,
+                cells.len()
+            )));
+        }
+        Ok(config)
+    }
+
+    pub fn cells(&self) -> usize {
+        self.size * self.size
+    }
+}
+
+// ----------------------------- 简易 TOML -----------------------------
+// 配置文件只用到 TOML 的一个小子集，不引入完整的解析库：
+// [表名] 表头、key = value 键值对、# 注释；值为带双引号的字符串、整数、浮点数或 true / false
+#[derive(Debug, Clone, PartialEq)]
+pub enum TomlValue {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+}
+
+impl TomlValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            TomlValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_usize(&self) -> Option<usize> {
+        match self {
+            TomlValue::Integer(i) => usize::try_from(*i).ok(),
+            _ => None,
+        }
+    }
+
+    // 整数也当作浮点数接受，写 font_size = 14 不必写成 14.0
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            TomlValue::Integer(i) => Some(*i as f64),
+            TomlValue::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            TomlValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+// 按出现顺序返回 (表名, 键, 值)，第一个表头之前的键表名为空字符串；错误信息中的行号从 1 开始
+pub fn parse_toml(text: &str) -> Result<Vec<(String, String, TomlValue)>, String> {
+    let is_bare_key = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    let mut table = String::new();
+    let mut entries = Vec::new();
+    for (i, raw) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix('[') {
+            let (name, tail) = rest.split_once(']').ok_or_else(|| format!("第 {} 行: 表头缺少 ]", line_no))?;
+            let name = name.trim();
+            if !is_bare_key(name) || !is_comment_or_empty(tail) {
+                return Err(format!("第 {} 行: 无法识别的表头 {}", line_no, line));
+            }
+            table = name.to_string();
+            continue;
+        }
+        let (key, value) = line.split_once('=').ok_or_else(|| format!("第 {} 行: 应为 key = value", line_no))?;
+        let key = key.trim();
+        if !is_bare_key(key) {
+            return Err(format!("第 {} 行: 键名 {} 只能包含字母、数字、_ 和 -", line_no, key));
+        }
+        let value = parse_toml_value(value.trim()).map_err(|e| format!("第 {} 行: {}", line_no, e))?;
+        entries.push((table.clone(), key.to_string(), value));
+    }
+    Ok(entries)
+}
+
+fn is_comment_or_empty(s: &str) -> bool {
+    let s = s.trim();
+    s.is_empty() || s.starts_with('#')
+}
+
+fn parse_toml_value(s: &str) -> Result<TomlValue, String> {
+    if let Some(rest) = s.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.chars();
+        loop {
+            match chars.next() {
+                None => return Err("字符串缺少结尾的引号".to_string()),
+                Some('"') => break,
+                Some('\\') => match chars.next() {
+                    Some('"') => value.push('"'),
+                    Some('\\') => value.push('\\'),
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    other => return Err(format!("不支持的转义 \\{}", other.map(String::from).unwrap_or_default())),
+                },
+                Some(c) => value.push(c),
+            }
+        }
+        if !is_comment_or_empty(chars.as_str()) {
+            return Err(format!("字符串之后有多余的内容: {}", chars.as_str().trim()));
+        }
+        return Ok(TomlValue::String(value));
+    }
+
+    let s = s.split_once('#').map_or(s, |(value, _)| value).trim();
+    match s {
+        "true" => return Ok(TomlValue::Boolean(true)),
+        "false" => return Ok(TomlValue::Boolean(false)),
+        _ => {}
+    }
+    let digits = s.replace('_', "");
+    if let Ok(i) = digits.parse::<i64>() {
+        return Ok(TomlValue::Integer(i));
+    }
+    match digits.parse::<f64>() {
+        Ok(f) if !s.is_empty() => Ok(TomlValue::Float(f)),
+        _ => Err(format!("无法识别的值: {}", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(table: &str, key: &str, value: TomlValue) -> (String, String, TomlValue) {
+        (table.to_string(), key.to_string(), value)
+    }
+
+    #[test]
+    fn parse_toml_reads_the_supported_subset() {
+        let text = r#"
+# 注释与空行被忽略
+output_dir = "out # 不是注释"
+max_checked = 1_000  # 行尾注释
+compress_json = true
+
+[colors]
+Red = 2
+min-white = -3
+font_size = 14.5
+escaped = "a\"b\\c\td"
+"#;
+        assert_eq!(
+            parse_toml(text),
+            Ok(vec![
+                entry("", "output_dir", TomlValue::String("out # 不是注释".to_string())),
+                entry("", "max_checked", TomlValue::Integer(1000)),
+                entry("", "compress_json", TomlValue::Boolean(true)),
+                entry("colors", "Red", TomlValue::Integer(2)),
+                entry("colors", "min-white", TomlValue::Integer(-3)),
+                entry("colors", "font_size", TomlValue::Float(14.5)),
+                entry("colors", "escaped", TomlValue::String("a\"b\\c\td".to_string())),
+            ])
+        );
+        assert_eq!(TomlValue::Integer(14).as_f64(), Some(14.0));
+        assert_eq!(TomlValue::Integer(-1).as_usize(), None);
+    }
+
+    #[test]
+    fn parse_toml_reports_the_line_of_each_error() {
+        let cases = [
+            ("a = 1\n[colors", "第 2 行: 表头缺少 ]"),
+            ("[a.b]", "第 1 行: 无法识别的表头 [a.b]"),
+            ("just a line", "第 1 行: 应为 key = value"),
+            ("\"quoted\" = 1", "第 1 行: 键名 \"quoted\" 只能包含字母、数字、_ 和 -"),
+            ("a = \"open", "第 1 行: 字符串缺少结尾的引号"),
+            ("a = \"x\\q\"", "第 1 行: 不支持的转义 \\q"),
+            ("a = \"x\" y", "第 1 行: 字符串之后有多余的内容: y"),
+            ("\n\na = yes", "第 3 行: 无法识别的值: yes"),
+            ("a =", "第 1 行: 无法识别的值: "),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(parse_toml(text), Err(expected.to_string()), "{:?}", text);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_load_overrides_only_the_given_keys() {
+        let dir = std::env::temp_dir().join(format!("bingo-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bingo.toml");
+        let path = path.to_str().unwrap();
+
+        assert_eq!(Config::load(&format!("{}.missing", path)).unwrap(), Config::default());
+
+        fs::write(path, "output_dir = \"out\"\ncell_size = 60\nrule_font_size = 12\nmax_checked = 9\n").unwrap();
+        let config = Config::load(path).unwrap();
+        assert_eq!(
+            config,
+            Config { output_dir: "out".to_string(), cell_size: 60, rule_font_size: 12.0, max_checked: Some(9), ..Config::default() }
+        );
+
+        fs::write(path, "cel_size = 60\n").unwrap();
+        assert!(matches!(Config::load(path), Err(BingoError::Format(_))));
+        fs::write(path, "cell_size = \"60\"\n").unwrap();
+        assert!(matches!(Config::load(path), Err(BingoError::Format(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn grid_config_rejects_sizes_outside_the_supported_range() {
+        assert!(matches!(GridConfig::new(0), Err(BingoError::InvalidInput(_))));
+        assert!(matches!(GridConfig::new(MAX_GRID_SIZE + 1), Err(BingoError::InvalidInput(_))));
+        assert_eq!(GridConfig::new(MAX_GRID_SIZE).unwrap().cells(), MAX_CELLS);
+        assert_eq!(GridConfig::of(&[vec![0; 3], vec![0; 3], vec![0; 3]]).unwrap(), GridConfig::new(3).unwrap());
+        assert!(matches!(GridConfig::of(&[vec![0; 2], vec![0; 3]]), Err(BingoError::InvalidInput(_))));
+    }
+}
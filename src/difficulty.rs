@@ -0,0 +1,126 @@
+use std::{fmt, str::FromStr};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::grid::{is_rule_free, Color, Grid};
+
+// ----------------------------- 难度评分 -----------------------------
+// 0.0 ~ 1.0 的连续评分，三项等权平均：
+// (a) 解数量的倒数，解越少越难（无解时记 0）
+// (b) 受规则约束的格子（非白、非黑）占全部格子的比例
+// (c) 规则之间的相互牵连：每个受约束格子所依赖的格子中，本身也受约束的比例，再对所有受约束格子取平均
+pub fn puzzle_difficulty(solutions: &[Grid], color_grid: &[Vec<Color>]) -> f64 {
+    let inverse_count = if solutions.is_empty() { 0.0 } else { 1.0 / solutions.len() as f64 };
+
+    let blank = Grid::new_blank(color_grid);
+    let constrained: Vec<(usize, usize)> = blank
+        .0
+        .iter()
+        .flatten()
+        .filter(|cell| !is_rule_free(cell.color))
+        .map(|cell| (cell.x, cell.y))
+        .collect();
+    let total_cells = color_grid.iter().map(Vec::len).sum::<usize>().max(1);
+    let density = constrained.len() as f64 / total_cells as f64;
+
+    let interactions: Vec<f64> = constrained
+        .iter()
+        .map(|&(x, y)| {
+            let others: Vec<(usize, usize)> =
+                blank.rule_dependencies(x, y).into_iter().filter(|&p| p != (x, y)).collect();
+            let linked = others.iter().filter(|(i, j)| !is_rule_free(color_grid[*i][*j])).count();
+            linked as f64 / others.len().max(1) as f64
+        })
+        .collect();
+    let interaction = if interactions.is_empty() {
+        0.0
+    } else {
+        interactions.iter().sum::<f64>() / interactions.len() as f64
+    };
+
+    ((inverse_count + density + interaction) / 3.0).clamp(0.0, 1.0)
+}
+
+// 同 puzzle_difficulty，颜色布局取自第一个解（同一题目的解颜色相同）；没有解时按空布局计算
+pub fn solutions_difficulty(solutions: &[Grid]) -> f64 {
+    let color_grid: Vec<Vec<Color>> = solutions
+        .first()
+        .map(|solution| solution.0.iter().map(|row| row.iter().map(|cell| cell.color).collect()).collect())
+        .unwrap_or_default();
+    puzzle_difficulty(solutions, &color_grid)
+}
+
+// ----------------------------- 难度档位 -----------------------------
+// 按 puzzle_difficulty 的评分分为四档，solutions.json、解列表和 data/index.json 检索都使用这一档位。分界点为一次抽样的四分位数：
+// 种子 1 起连续生成、通过 generate 全部筛选条件（不限勾选数）的前 1000 道 5 × 5 题目，
+// 评分的 25%、50%、75% 分位分别约为 0.536、0.598、0.715（最低 0.334，最高 0.959）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DifficultyTier {
+    Easy,
+    Medium,
+    Hard,
+    Extreme,
+}
+
+pub fn classify_difficulty(score: f64) -> DifficultyTier {
+    if score < 0.536 {
+        DifficultyTier::Easy
+    } else if score < 0.598 {
+        DifficultyTier::Medium
+    } else if score < 0.715 {
+        DifficultyTier::Hard
+    } else {
+        DifficultyTier::Extreme
+    }
+}
+
+impl FromStr for DifficultyTier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" => Ok(DifficultyTier::Easy),
+            "medium" => Ok(DifficultyTier::Medium),
+            "hard" => Ok(DifficultyTier::Hard),
+            "extreme" => Ok(DifficultyTier::Extreme),
+            _ => Err(format!("未知的难度档位 {}，可选: easy, medium, hard, extreme", s)),
+        }
+    }
+}
+
+impl fmt::Display for DifficultyTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DifficultyTier::Easy => "Easy",
+            DifficultyTier::Medium => "Medium",
+            DifficultyTier::Hard => "Hard",
+            DifficultyTier::Extreme => "Extreme",
+        };
+        f.pad(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tier_thresholds_are_quartile_boundaries() {
+        assert_eq!(classify_difficulty(0.0), DifficultyTier::Easy);
+        assert_eq!(classify_difficulty(0.535), DifficultyTier::Easy);
+        assert_eq!(classify_difficulty(0.536), DifficultyTier::Medium);
+        assert_eq!(classify_difficulty(0.598), DifficultyTier::Hard);
+        assert_eq!(classify_difficulty(0.715), DifficultyTier::Extreme);
+        assert_eq!(classify_difficulty(1.0), DifficultyTier::Extreme);
+    }
+
+    #[test]
+    fn tier_names_round_trip() {
+        for tier in [DifficultyTier::Easy, DifficultyTier::Medium, DifficultyTier::Hard, DifficultyTier::Extreme] {
+            assert_eq!(tier.to_string().parse::<DifficultyTier>(), Ok(tier));
+        }
+        assert!("impossible".parse::<DifficultyTier>().is_err());
+    }
+}
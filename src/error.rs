@@ -0,0 +1,192 @@
+use std::{fmt, io};
+
+use crate::config::MAX_GRID_SIZE;
+
+// ----------------------------- 错误类型 -----------------------------
+#[derive(Debug)]
+pub enum BingoError {
+    Io(io::Error),
+    Image(image::ImageError),
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+    Format(String), // 数据内容不符合约定的格式
+    FontLoad,       // 字体数据无法解析
+    FontMissing,    // 没有内置字体（未启用 bundled-font），也没有配置 font_path
+    DateFolderExists(String), // 日期文件夹已存在且未允许覆盖，内容为文件夹路径
+    Parse(ParseError),
+    InvalidInput(String),     // 命令行给出的输入不可用（尺寸不符、题目无解等），内容为原因
+    Context(String, Box<BingoError>), // 出错的步骤说明及原始错误，见 ResultExt::context
+}
+
+impl fmt::Display for BingoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BingoError::Io(e) => write!(f, "文件读写失败: {}", e),
+            BingoError::Image(e) => write!(f, "图片处理失败: {}", e),
+            #[cfg(feature = "serde")]
+            BingoError::Json(e) => write!(f, "JSON 解析失败: {}", e),
+            BingoError::Format(msg) => write!(f, "数据格式错误: {}", msg),
+            BingoError::FontLoad => write!(f, "字体加载失败: 字体文件不是有效的 TrueType 字体"),
+            BingoError::FontMissing => {
+                write!(f, "字体加载失败: 编译时未启用 bundled-font，请在 bingo.toml 中用 font_path 指定字体文件")
+            }
+            BingoError::DateFolderExists(path) => {
+                write!(f, "日期文件夹 {} 已存在，如需覆盖请加 --overwrite（其中手动放入的文件会被删除）", path)
+            }
+            BingoError::Parse(e) => write!(f, "颜色布局解析失败: {}", e),
+            BingoError::InvalidInput(msg) => write!(f, "输入无效: {}", msg),
+            BingoError::Context(what, e) => write!(f, "{}: {}", what, e),
+        }
+    }
+}
+
+impl std::error::Error for BingoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BingoError::Io(e) => Some(e),
+            BingoError::Image(e) => Some(e),
+            #[cfg(feature = "serde")]
+            BingoError::Json(e) => Some(e),
+            BingoError::Parse(e) => Some(e),
+            BingoError::Context(_, e) => Some(e.as_ref()),
+            BingoError::Format(_)
+            | BingoError::FontLoad
+            | BingoError::FontMissing
+            | BingoError::DateFolderExists(_)
+            | BingoError::InvalidInput(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for BingoError {
+    fn from(e: io::Error) -> Self {
+        BingoError::Io(e)
+    }
+}
+
+impl From<image::ImageError> for BingoError {
+    fn from(e: image::ImageError) -> Self {
+        BingoError::Image(e)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for BingoError {
+    fn from(e: serde_json::Error) -> Self {
+        BingoError::Json(e)
+    }
+}
+
+impl From<ParseError> for BingoError {
+    fn from(e: ParseError) -> Self {
+        BingoError::Parse(e)
+    }
+}
+
+// 给错误加上出错步骤的说明，例如 fs::read_to_string(path).context("无法读取颜色布局文件")?
+pub trait ResultExt<T> {
+    fn context(self, what: impl Into<String>) -> Result<T, BingoError>;
+}
+
+impl<T, E: Into<BingoError>> ResultExt<T> for Result<T, E> {
+    fn context(self, what: impl Into<String>) -> Result<T, BingoError> {
+        self.map_err(|e| BingoError::Context(what.into(), Box::new(e.into())))
+    }
+}
+
+// ----------------------------- 文本布局解析错误 -----------------------------
+// Grid::from_string 的错误，行列号均从 0 开始
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    UnknownColorChar { row: usize, col: usize, found: char },
+    WrongRowCount { found: usize },                              // 行数为 0 或超过最大边长
+    WrongColCount { row: usize, expected: usize, found: usize }, // 网格须为方形，每行字母数等于行数
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnknownColorChar { row, col, found } => {
+                write!(f, "第 {} 行第 {} 列的字符 '{}' 不对应任何颜色", row, col, found)
+            }
+            ParseError::WrongRowCount { found } => {
+                write!(f, "行数应在 1..={} 之间，实际为 {}", MAX_GRID_SIZE, found)
+            }
+            ParseError::WrongColCount { row, expected, found } => {
+                write!(f, "第 {} 行应有 {} 个字母，实际为 {}", row, expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// ----------------------------- 分享参数解码错误 -----------------------------
+// grid_from_url_param 的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    Empty,
+    InvalidCharacter { position: usize, found: char }, // 不属于 URL 安全 base64 字母表的字符
+    Truncated,                                          // base64 长度不完整
+    InvalidSize(usize),                                 // 边长为 0 或超过最大边长
+    WrongLength { expected: usize, found: usize },      // 格子数据的字节数与边长不符
+    UnknownColorIndex { cell: usize, index: u8 },       // 按行优先顺序的第 cell 个格子
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Empty => write!(f, "分享参数为空"),
+            DecodeError::InvalidCharacter { position, found } => {
+                write!(f, "第 {} 个字符 '{}' 不是合法的 base64 字符", position, found)
+            }
+            DecodeError::Truncated => write!(f, "分享参数长度不完整，可能被截断"),
+            DecodeError::InvalidSize(size) => write!(f, "边长应在 1..={} 之间，实际为 {}", MAX_GRID_SIZE, size),
+            DecodeError::WrongLength { expected, found } => {
+                write!(f, "格子数据应为 {} 字节，实际为 {}", expected, found)
+            }
+            DecodeError::UnknownColorIndex { cell, index } => {
+                write!(f, "第 {} 个格子的颜色编号 {} 不对应任何颜色", cell, index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+// ----------------------------- CSV 解析错误 -----------------------------
+// Grid::from_csv 的错误，行列号均从 0 开始
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvParseError {
+    WrongRowCount { found: usize },                              // 行数为 0 或超过最大边长
+    WrongColCount { row: usize, expected: usize, found: usize }, // 网格须为方形，每行单元格数等于行数
+    UnterminatedQuote { row: usize },
+    MalformedCell { row: usize, col: usize, found: String },   // 不是"颜色,勾选"的形式
+    UnknownColor { row: usize, col: usize, found: String },
+    InvalidChecked { row: usize, col: usize, found: String },  // 勾选状态只能是 true / false
+}
+
+impl fmt::Display for CsvParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvParseError::WrongRowCount { found } => {
+                write!(f, "行数应在 1..={} 之间，实际为 {}", MAX_GRID_SIZE, found)
+            }
+            CsvParseError::WrongColCount { row, expected, found } => {
+                write!(f, "第 {} 行应有 {} 个单元格，实际为 {}", row, expected, found)
+            }
+            CsvParseError::UnterminatedQuote { row } => write!(f, "第 {} 行的引号没有闭合", row),
+            CsvParseError::MalformedCell { row, col, found } => {
+                write!(f, "第 {} 行第 {} 列的内容 \"{}\" 应为 颜色,勾选 的形式", row, col, found)
+            }
+            CsvParseError::UnknownColor { row, col, found } => {
+                write!(f, "第 {} 行第 {} 列的颜色 {} 不存在", row, col, found)
+            }
+            CsvParseError::InvalidChecked { row, col, found } => {
+                write!(f, "第 {} 行第 {} 列的勾选状态 {} 应为 true 或 false", row, col, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CsvParseError {}
@@ -0,0 +1,444 @@
+use chrono::NaiveDate;
+use log::{debug, info};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::{collections::HashMap, fs};
+
+use crate::config::{parse_toml, GridConfig, DEFAULT_MAX_BLACK, DEFAULT_MIN_WHITE};
+use crate::error::BingoError;
+use crate::grid::{is_rule_free, Color, Grid};
+use crate::solver::Solver;
+
+// ----------------------------- 工具函数 -----------------------------
+pub fn generate_color_grid(config: &GridConfig) -> Vec<Vec<Color>> {
+    generate_color_grid_with(config, &mut rand::thread_rng())
+}
+
+// 同一个种子总是生成同一个布局
+pub fn generate_color_grid_seeded(config: &GridConfig, seed: u64) -> Vec<Vec<Color>> {
+    generate_color_grid_with(config, &mut StdRng::seed_from_u64(seed))
+}
+
+// 需要连续生成多个可复现的布局时（如重试），由调用方持有同一个 rng
+pub fn generate_color_grid_with(config: &GridConfig, rng: &mut impl Rng) -> Vec<Vec<Color>> {
+    generate_color_grid_min_white(config, DEFAULT_MIN_WHITE, rng)
+}
+
+// 与 generate_color_grid_with 相同，白格至少 min_white 个（超过格子数时按格子数处理）
+pub fn generate_color_grid_min_white(config: &GridConfig, min_white: usize, rng: &mut impl Rng) -> Vec<Vec<Color>> {
+    generate_color_grid_limited(config, min_white, DEFAULT_MAX_BLACK, rng)
+}
+
+// 白格至少 min_white 个，黑格至多 max_black 个（见 DEFAULT_MAX_BLACK）
+pub fn generate_color_grid_limited(
+    config: &GridConfig,
+    min_white: usize,
+    max_black: usize,
+    rng: &mut impl Rng,
+) -> Vec<Vec<Color>> {
+    let n = config.size;
+    let min_white = min_white.min(config.cells());
+    let colors = vec![
+        Color::Red, Color::Blue, Color::Black,
+        Color::Green, Color::Yellow, Color::Purple, 
+        Color::White, Color::Orange, Color::Cyan,
+        Color::Teal, Color::Pink, Color::Gray,
+        Color::Maroon, Color::Navy,
+    ];
+    
+    // 生成初始随机网格
+    let mut grid: Vec<Vec<Color>> = (0..n)
+        .map(|_| (0..n).map(|_| *colors.choose(rng).unwrap()).collect())
+        .collect();
+
+    // 超出 max_black 的黑格（按行优先顺序保留前 max_black 个）在其余颜色中重新抽取。
+    // 黑格不超限时不消耗随机数，同一种子生成的布局与加入上限之前相同
+    let non_black: Vec<Color> = colors.iter().copied().filter(|c| *c != Color::Black).collect();
+    let mut black_count = 0;
+    for color in grid.iter_mut().flatten() {
+        if *color == Color::Black {
+            black_count += 1;
+            if black_count > max_black {
+                *color = *non_black.choose(rng).unwrap();
+            }
+        }
+    }
+
+    // 强制至少有 min_white 个白格
+    // 注意：黑格上限和白格下限都会让颜色频率偏离均匀抽取的 1/14。5 × 5、默认参数下黑格约 5.4%，
+    // 白格约 9.7%；超限黑格在其余颜色中均匀改抽，换成白格的位置也是随机的，对其余颜色的影响相同，
+    // 因此其余颜色彼此之间仍是均匀的（各约 7.08%）
+    let mut white_count = grid.iter().flatten().filter(|c| **c == Color::White).count();
+    while white_count < min_white {
+        let x = rng.gen_range(0..n);
+        let y = rng.gen_range(0..n);
+        if grid[x][y] != Color::White {
+            grid[x][y] = Color::White;
+            white_count += 1;
+        }
+    }
+
+    grid
+}
+
+// 同色格子互不相邻（上下左右，斜向不算）的布局：按行优先顺序逐格抽取，每格只在与上方、左方格子不同的颜色中均匀抽取，
+// 黑格达到 max_black 个后不再抽到黑格，一遍即可生成，不需要拒绝采样。之后再把不与任何白格相邻的格子改成白格，
+// 直到白格有 min_white 个；放不下时白格数可能少于 min_white。得到的布局不是在所有合法布局中均匀分布的，
+// 但每种颜色出现的机会大致相同
+pub fn generate_color_grid_nonadjacent(
+    config: &GridConfig,
+    min_white: usize,
+    max_black: usize,
+    rng: &mut impl Rng,
+) -> Vec<Vec<Color>> {
+    let n = config.size;
+    let mut grid: Vec<Vec<Color>> = Vec::with_capacity(n);
+    let mut black_count = 0;
+    for x in 0..n {
+        let mut row: Vec<Color> = Vec::with_capacity(n);
+        for y in 0..n {
+            let above = (x > 0).then(|| grid[x - 1][y]);
+            let left = (y > 0).then(|| row[y - 1]);
+            let allowed: Vec<Color> = Color::ALL
+                .into_iter()
+                .filter(|color| Some(*color) != above && Some(*color) != left)
+                .filter(|color| *color != Color::Black || black_count < max_black)
+                .collect();
+            let color = *allowed.choose(rng).unwrap();
+            black_count += usize::from(color == Color::Black);
+            row.push(color);
+        }
+        grid.push(row);
+    }
+
+    let blank = Grid::new_blank(&grid);
+    let mut white_count = grid.iter().flatten().filter(|c| **c == Color::White).count();
+    while white_count < min_white {
+        let candidates: Vec<(usize, usize)> = (0..n * n)
+            .map(|i| (i / n, i % n))
+            .filter(|&(x, y)| grid[x][y] != Color::White)
+            .filter(|&(x, y)| blank.get_four_neighbors(x, y).iter().all(|&(i, j)| grid[i][j] != Color::White))
+            .collect();
+        let Some(&(x, y)) = candidates.choose(rng) else {
+            debug!("白格已无处可放，只有 {} 个（要求 {} 个）", white_count, min_white);
+            break;
+        };
+        grid[x][y] = Color::White;
+        white_count += 1;
+    }
+
+    grid
+}
+
+// ----------------------------- 颜色数量约束 -----------------------------
+// 每种颜色在布局中出现次数的上下限，没有列出的颜色下限为 0、上限不限
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorConstraints {
+    pub min: HashMap<Color, usize>,
+    pub max: HashMap<Color, usize>,
+}
+
+// 默认：黑格至多 2 个，其余每种有规则的颜色至少 1 个，白格至少 2 个（与 generate_color_grid 一致），任何颜色至多 10 个。
+// 有规则的颜色共 12 种，加上白格的下限共 14 个格子，因此默认约束只适用于 4 × 4 及以上的网格
+impl Default for ColorConstraints {
+    fn default() -> Self {
+        let mut min: HashMap<Color, usize> = Color::ALL
+            .into_iter()
+            .filter(|color| !is_rule_free(*color))
+            .map(|color| (color, 1))
+            .collect();
+        min.insert(Color::White, 2);
+        let mut max: HashMap<Color, usize> = Color::ALL.into_iter().map(|color| (color, 10)).collect();
+        max.insert(Color::Black, 2);
+        Self { min, max }
+    }
+}
+
+impl ColorConstraints {
+    // 颜色数量约束文件，[min]、[max] 两张表，键为颜色的英文名（不区分大小写），例如：
+    //   [min]
+    //   Red = 2
+    //   [max]
+    //   Black = 1
+    // 文件中列出的颜色覆盖默认约束，没有列出的颜色沿用默认值
+    pub fn from_toml(text: &str) -> Result<Self, BingoError> {
+        let mut constraints = Self::default();
+        for (table, key, value) in parse_toml(text).map_err(BingoError::Format)? {
+            let color = Color::from_name(&key).ok_or_else(|| BingoError::Format(format!("未知的颜色: {}", key)))?;
+            let count = value
+                .as_usize()
+                .ok_or_else(|| BingoError::Format(format!("{}.{} 应为非负整数", table, key)))?;
+            match table.as_str() {
+                "min" => constraints.min.insert(color, count),
+                "max" => constraints.max.insert(color, count),
+                _ => return Err(BingoError::Format(format!("未知的表 [{}]，只支持 [min] 和 [max]", table))),
+            };
+        }
+        Ok(constraints)
+    }
+
+    pub fn load(path: &str) -> Result<Self, BingoError> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    fn bounds(&self, color: Color, cells: usize) -> (usize, usize) {
+        (self.min.get(&color).copied().unwrap_or(0), self.max.get(&color).copied().unwrap_or(cells))
+    }
+
+    // 共 cells 个格子时约束能否同时满足：每种颜色下限不超过上限，下限之和不超过格子数，上限之和不少于格子数
+    pub fn check_feasible(&self, cells: usize) -> Result<(), String> {
+        for color in Color::ALL {
+            let (min, max) = self.bounds(color, cells);
+            if min > max {
+                return Err(format!("{}格的下限 {} 大于上限 {}", color.label(), min, max));
+            }
+        }
+        let min_total: usize = Color::ALL.iter().map(|color| self.bounds(*color, cells).0).sum();
+        let max_total: usize = Color::ALL.iter().map(|color| self.bounds(*color, cells).1.min(cells)).sum();
+        if min_total > cells {
+            return Err(format!("各颜色下限之和 {} 超过格子总数 {}", min_total, cells));
+        }
+        if max_total < cells {
+            return Err(format!("各颜色上限之和 {} 不足格子总数 {}", max_total, cells));
+        }
+        Ok(())
+    }
+}
+
+// 先按下限放入每种颜色，剩下的格子从尚未达到上限的颜色中均匀抽取，最后整体打乱位置。
+// 约束无法满足时（见 check_feasible）返回 InvalidInput
+pub fn generate_color_grid_constrained(
+    config: &GridConfig,
+    constraints: &ColorConstraints,
+    rng: &mut impl Rng,
+) -> Result<Vec<Vec<Color>>, BingoError> {
+    let cells = config.cells();
+    constraints
+        .check_feasible(cells)
+        .map_err(|e| BingoError::InvalidInput(format!("颜色数量约束无法满足: {}", e)))?;
+    let mut counts: HashMap<Color, usize> = HashMap::new();
+    let mut pool: Vec<Color> = Vec::with_capacity(cells);
+    for color in Color::ALL {
+        let (min, _) = constraints.bounds(color, cells);
+        pool.extend(std::iter::repeat_n(color, min));
+        counts.insert(color, min);
+    }
+    while pool.len() < cells {
+        let open: Vec<Color> = Color::ALL
+            .into_iter()
+            .filter(|color| counts[color] < constraints.bounds(*color, cells).1)
+            .collect();
+        let color = *open.choose(rng).unwrap();
+        pool.push(color);
+        *counts.get_mut(&color).unwrap() += 1;
+    }
+    pool.shuffle(rng);
+    Ok(pool.chunks(config.size).map(<[Color]>::to_vec).collect())
+}
+
+// 形如 20260309 的种子对应日期 2026-03-09，CI 可以用日期作种子复现历史题目；其他种子返回 None
+pub fn date_from_seed(seed: u64) -> Option<String> {
+    let (year, month, day) = (seed / 10_000, (seed / 100 % 100) as u32, (seed % 100) as u32);
+    NaiveDate::from_ymd_opt(i32::try_from(year).ok()?, month, day).map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+// 生成预筛：至少有一行和一列能够全部勾选而不与任何规则矛盾，对角线不计入。
+// 比"至少一条五连"更严格，会多筛掉一些本来有解的布局，换来行、列两个方向都有可达的获胜线
+pub fn has_completeable_line(color_grid: &[Vec<Color>]) -> Result<bool, BingoError> {
+    let solver = Solver::builder(color_grid.to_vec()).build()?;
+    let completeable: Vec<String> = Grid::new_blank(color_grid)
+        .iter_lines()
+        .filter(|line| solver.line_completeable(line))
+        .map(|line| describe_line(&line))
+        .collect();
+    info!("可以全部勾选的五连: {:?}", completeable);
+    let has = |kind: &str| completeable.iter().any(|line| line.ends_with(kind));
+    Ok(has("行") && has("列"))
+}
+
+// 日志中使用的五连名称
+fn describe_line(line: &[(usize, usize)]) -> String {
+    let (x0, y0) = line[0];
+    if line.iter().all(|(i, _)| *i == x0) {
+        format!("第{}行", x0)
+    } else if line.iter().all(|(_, j)| *j == y0) {
+        format!("第{}列", y0)
+    } else if y0 == 0 {
+        "主对角线".to_string()
+    } else {
+        "副对角线".to_string()
+    }
+}
+
+// 绿格要求所在行与所在列的勾选数相等，同一行（或列）上的多个绿格会把若干行列串成一组，
+// 组内所有行列的勾选数必须相同。用并查集合并行列（0..n 为行，n..2n 为列），
+// 再用必然勾选（黑格）与必然不勾选的格子给每行每列的勾选数定上下界，组内区间无交集即矛盾。
+// 灰格、藏格要求所在行、列勾选过半，同样计入下界（即使没有绿格，单独一行一列的区间为空也是矛盾）
+pub fn detect_green_contradictions(color_grid: &[Vec<Color>]) -> bool {
+    let n = color_grid.len();
+    let mut parent: Vec<usize> = (0..2 * n).collect();
+    fn find(parent: &mut [usize], i: usize) -> usize {
+        if parent[i] != i {
+            parent[i] = find(parent, parent[i]);
+        }
+        parent[i]
+    }
+
+    for (x, row) in color_grid.iter().enumerate() {
+        for (y, color) in row.iter().enumerate() {
+            if *color == Color::Green {
+                let (a, b) = (find(&mut parent, x), find(&mut parent, n + y));
+                parent[a] = b;
+            }
+        }
+    }
+
+    // 必然不勾选：周围已有两个黑格的蓝格，其余非黑邻居都不能再勾；粉格的邻居一律不能勾
+    let grid = Grid::new_blank(color_grid);
+    let is_black = |(i, j): (usize, usize)| color_grid[i][j] == Color::Black;
+    let mut forced_unchecked = vec![vec![false; n]; n];
+    for (x, row) in color_grid.iter().enumerate() {
+        for (y, color) in row.iter().enumerate() {
+            let neighbors = grid.get_neighbors(x, y);
+            if *color == Color::Blue && neighbors.iter().filter(|p| is_black(**p)).count() == 2 {
+                for &(i, j) in neighbors.iter().filter(|p| !is_black(**p)) {
+                    forced_unchecked[i][j] = true;
+                }
+            }
+            if *color == Color::Pink {
+                for &(i, j) in &neighbors {
+                    forced_unchecked[i][j] = true;
+                }
+            }
+        }
+    }
+
+    let line_cells = |line: usize| -> Vec<(usize, usize)> {
+        if line < n { (0..n).map(|j| (line, j)).collect() } else { (0..n).map(|i| (i, line - n)).collect() }
+    };
+    let majority_color = |line: usize| if line < n { Color::Gray } else { Color::Navy };
+    let mut bounds = vec![(0usize, n); 2 * n]; // 每个组的 (下界, 上界)
+    for line in 0..2 * n {
+        let cells = line_cells(line);
+        let mut low = cells.iter().filter(|p| is_black(**p)).count();
+        if cells.iter().any(|(i, j)| color_grid[*i][*j] == majority_color(line)) {
+            low = low.max(n / 2 + 1);
+        }
+        let high = n - cells.iter().filter(|(i, j)| forced_unchecked[*i][*j]).count();
+        let root = find(&mut parent, line);
+        bounds[root] = (bounds[root].0.max(low), bounds[root].1.min(high));
+    }
+
+    let contradiction = (0..2 * n).any(|line| {
+        let (low, high) = bounds[line];
+        find(&mut parent, line) == line && low > high
+    });
+    if contradiction {
+        debug!("❌ 绿格约束矛盾：同组行列的勾选数不可能相等");
+    }
+    contradiction
+}
+
+// 只保留五连中至少包含一个受规则约束格子（非白、非黑）的解；
+// 五连全部落在白格上时规则对玩家毫无帮助，这类题目没有意思
+pub fn solutions_with_constrained_win_line(solutions: &[Grid], color_grid: &[Vec<Color>]) -> Vec<Grid> {
+    solutions
+        .iter()
+        .filter(|solution| {
+            solution.winning_lines().iter().any(|line| {
+                line.iter().any(|(i, j)| !is_rule_free(color_grid[*i][*j]))
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+pub fn format_grid_colors(grid: &[Vec<Color>]) -> String {
+    grid.iter().map(|row| {
+        row.iter().map(|color| color.label()).collect::<Vec<_>>().join(" ")
+    }).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constrained_generation_respects_bounds() {
+        let config = GridConfig::new(5).unwrap();
+        let constraints = ColorConstraints::default();
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..50 {
+            let grid = generate_color_grid_constrained(&config, &constraints, &mut rng).unwrap();
+            for color in Color::ALL {
+                let count = grid.iter().flatten().filter(|c| **c == color).count();
+                let (min, max) = constraints.bounds(color, config.cells());
+                assert!((min..=max).contains(&count), "{:?} 出现 {} 次", color, count);
+            }
+        }
+    }
+
+    #[test]
+    fn infeasible_constraints_are_an_error() {
+        let mut constraints = ColorConstraints::default();
+        constraints.min.insert(Color::Red, 20);
+        let result = generate_color_grid_constrained(&GridConfig::new(5).unwrap(), &constraints, &mut StdRng::seed_from_u64(1));
+        assert!(matches!(result, Err(BingoError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn majority_rows_and_columns_count_as_lower_bounds() {
+        // 藏格所在的第 2 列须勾选至少 3 格，但两个粉格让这一列的 (0,2)、(2,2)、(4,2) 都不能勾
+        let layout = ["WWNWW", "WWIWW", "WWWWW", "WWIWW", "WWWWW"];
+        let color_grid: Vec<Vec<Color>> =
+            layout.iter().map(|row| row.chars().map(|c| Color::from_char(c).unwrap()).collect()).collect();
+        assert!(detect_green_contradictions(&color_grid));
+
+        let mut relaxed = color_grid.clone();
+        relaxed[3][2] = Color::White;
+        assert!(!detect_green_contradictions(&relaxed));
+    }
+
+    // 10000 个 5 × 5 布局共 250000 个格子。黑格上限、白格下限会让这两种颜色偏离均匀（见 generate_color_grid_limited），
+    // 其余颜色彼此之间应当均匀：每种颜色的次数都在平均值的 3 倍标准差以内，整体的卡方统计量也在 0.1% 显著性水平之内
+    #[test]
+    fn generated_colors_other_than_black_and_white_are_uniform() {
+        let config = GridConfig::new(5).unwrap();
+        let mut rng = StdRng::seed_from_u64(2025);
+        let mut counts: HashMap<Color, usize> = HashMap::new();
+        for _ in 0..10_000 {
+            for color in generate_color_grid_with(&config, &mut rng).into_iter().flatten() {
+                *counts.entry(color).or_default() += 1;
+            }
+        }
+        let total = 250_000.0;
+        let share = |color: Color| counts[&color] as f64 / total;
+        let uniform = 1.0 / Color::ALL.len() as f64;
+        assert!(share(Color::Black) < uniform, "黑格占比 {}", share(Color::Black));
+        assert!(share(Color::White) > uniform, "白格占比 {}", share(Color::White));
+
+        let others: Vec<f64> =
+            Color::ALL.iter().filter(|color| !is_rule_free(**color)).map(|color| counts[color] as f64).collect();
+        let expected = others.iter().sum::<f64>() / others.len() as f64;
+        let p = expected / total;
+        let sigma = (total * p * (1.0 - p)).sqrt();
+        for (color, observed) in Color::ALL.iter().filter(|color| !is_rule_free(**color)).zip(&others) {
+            assert!((observed - expected).abs() < 3.0 * sigma, "{:?} 出现 {} 次，平均 {:.0}", color, observed, expected);
+        }
+        // 12 种颜色，自由度 11，0.1% 显著性水平的临界值为 31.26；增减颜色时须换用对应自由度的临界值
+        assert_eq!(others.len(), 12);
+        let chi_squared: f64 = others.iter().map(|observed| (observed - expected).powi(2) / expected).sum();
+        assert!(chi_squared < 31.26, "卡方统计量 {:.2}", chi_squared);
+    }
+
+    #[test]
+    fn completeable_lines_need_both_a_row_and_a_column() {
+        let layout = |s: &str| -> Vec<Vec<Color>> {
+            Grid::from_string(s).unwrap().0.iter().map(|row| row.iter().map(|cell| cell.color).collect()).collect()
+        };
+        assert!(has_completeable_line(&layout("WWWWW\nWWWWW\nWWWWW\nWWWWW\nWWWWW")).unwrap());
+        // 第 0 行全是粉格：任何一列勾满都会勾到粉格下方的邻居，只有第 2 ~ 4 行可以勾满
+        assert!(!has_completeable_line(&layout("IIIII\nWWWWW\nWWWWW\nWWWWW\nWWWWW")).unwrap());
+        // 转置后只有列可以勾满
+        assert!(!has_completeable_line(&layout("IWWWW\nIWWWW\nIWWWW\nIWWWW\nIWWWW")).unwrap());
+    }
+}
@@ -0,0 +1,1754 @@
+#[cfg(feature = "serde")]
+use serde::{Serialize, Deserialize};
+use log::debug;
+use rand::Rng;
+use std::{collections::{HashMap, HashSet}, fmt};
+
+use crate::error::{BingoError, CsvParseError, ParseError};
+use crate::config::{GridConfig, MAX_GRID_SIZE};
+use crate::profiling::profile_rule;
+use crate::solver::Solver;
+
+// ----------------------------- 数据结构定义 -----------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Color {
+    Red,
+    Blue,
+    Black,
+    Green,
+    Yellow,
+    Purple,
+    White,
+    Orange,
+    Cyan,
+    Teal,
+    Pink,
+    Gray,
+    Maroon,
+    Navy,
+}
+
+impl Color {
+    pub const ALL: [Color; 14] = [
+        Color::Red, Color::Blue, Color::Black,
+        Color::Green, Color::Yellow, Color::Purple,
+        Color::White, Color::Orange, Color::Cyan,
+        Color::Teal, Color::Pink, Color::Gray,
+        Color::Maroon, Color::Navy,
+    ];
+
+    // 日志中使用的单字中文名
+    pub fn label(self) -> &'static str {
+        match self {
+            Color::Red => "红",
+            Color::Blue => "蓝",
+            Color::Black => "黑",
+            Color::Green => "绿",
+            Color::Yellow => "黄",
+            Color::Purple => "紫",
+            Color::White => "白",
+            Color::Orange => "橙",
+            Color::Cyan => "青",
+            Color::Teal => "碧",
+            Color::Pink => "粉",
+            Color::Gray => "灰",
+            Color::Maroon => "栗",
+            Color::Navy => "藏",
+        }
+    }
+
+    // 紧凑格式中使用的单个 ASCII 字母（黑为 K，碧为 T，粉为 I，灰为 A，栗为 M，藏为 N）
+    pub fn to_char(self) -> char {
+        match self {
+            Color::Red => 'R',
+            Color::Blue => 'B',
+            Color::Black => 'K',
+            Color::Green => 'G',
+            Color::Yellow => 'Y',
+            Color::Purple => 'P',
+            Color::White => 'W',
+            Color::Orange => 'O',
+            Color::Cyan => 'C',
+            Color::Teal => 'T',
+            Color::Pink => 'I',
+            Color::Gray => 'A',
+            Color::Maroon => 'M',
+            Color::Navy => 'N',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Color> {
+        Color::ALL.into_iter().find(|color| color.to_char() == c)
+    }
+
+    // 按英文名（即 JSON 中的写法，如 Red、Teal）查找，不区分大小写
+    pub fn from_name(name: &str) -> Option<Color> {
+        Color::ALL.into_iter().find(|color| format!("{:?}", color).eq_ignore_ascii_case(name))
+    }
+}
+
+// 白格没有规则；黑格固定勾选、同样没有规则。这两种格子在规则校验中可以整体跳过
+pub fn is_rule_free(color: Color) -> bool {
+    matches!(color, Color::White | Color::Black)
+}
+
+// 各颜色规则的说明文字（按图片中规则栏的宽度分行），白格没有规则
+pub fn cell_rule_description(color: Color) -> &'static [&'static str] {
+    match color {
+        Color::Red => &["红格周围至少有一个被勾选的格子。"],
+        Color::Blue => &["蓝格周围勾选的格子不得超过两个。"],
+        Color::Black => &["黑格必须勾。"],
+        Color::Green => &["绿格所在行的勾选总数", "须等于所在列的勾选总数。"],
+        Color::Yellow => &["黄格所在两条交叉对角线", "（从黄格向四角延伸）的勾选总数必须相等。"],
+        Color::Purple => &["紫格周围被勾选的格子数量须为奇数。"],
+        Color::White => &[],
+        Color::Orange => &["橙格周围勾选的格子数量须为偶数。"],
+        Color::Cyan => &["青格如果被勾选，则其上下左右（不包括对角）", "至少有一个被勾选的格子。"],
+        Color::Teal => &["碧格所在的左上至右下对角线", "（包括碧格自己）勾选总数须为偶数。"],
+        Color::Pink => &["粉格周围不能有任何被勾选的格子。"],
+        Color::Gray => &["灰格所在行勾选的格子须多于未勾选的格子。"],
+        Color::Maroon => &["栗格如果被勾选，则网格的四个角", "至少有一个被勾选。"],
+        Color::Navy => &["藏格所在列勾选的格子须多于未勾选的格子。"],
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Cell {
+    pub x: usize,
+    pub y: usize,
+    pub color: Color,
+    pub checked: bool,
+}
+
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Grid(pub Vec<Vec<Cell>>);
+
+// 每个格子所在对角线的编号，均为 0..DIAGONALS，按最大边长编号，与网格实际尺寸无关：
+// diag1 为左上至右下方向（x - y + MAX_GRID_SIZE - 1），diag2 为右上至左下方向（x + y）
+pub const DIAGONALS: usize = 2 * MAX_GRID_SIZE - 1;
+
+#[inline(always)]
+pub const fn diag1_index(x: usize, y: usize) -> usize {
+    x + MAX_GRID_SIZE - 1 - y
+}
+
+#[inline(always)]
+pub const fn diag2_index(x: usize, y: usize) -> usize {
+    x + y
+}
+
+// 当前勾选状态下每条对角线的勾选数，按 diag1_index / diag2_index 的编号索引
+pub struct DiagonalCounts {
+    pub diag1: [u8; DIAGONALS],
+    pub diag2: [u8; DIAGONALS],
+}
+
+// 紧凑的文本表格：每格为颜色名加勾选标记（✓ 已勾选，· 未勾选）
+pub fn format_grid_ascii(grid: &Grid) -> String {
+    grid.0.iter().map(|row| {
+        row.iter()
+            .map(|cell| format!("{}{}", cell.color.label(), if cell.checked { "✓" } else { "·" }))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }).collect::<Vec<_>>().join("\n")
+}
+
+impl fmt::Debug for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format_grid_ascii(self))
+    }
+}
+
+// ----------------------------- 对称变换 -----------------------------
+// 正方形的 8 种对称（4 种旋转、4 种翻转）之一把 (x, y) 映射到的位置，k 取 0..8，k = 0 为恒等变换
+fn dihedral(k: usize, x: usize, y: usize, n: usize) -> (usize, usize) {
+    let (x, y) = if k & 4 != 0 { (x, n - 1 - y) } else { (x, y) };
+    match k & 3 {
+        0 => (x, y),
+        1 => (y, n - 1 - x),
+        2 => (n - 1 - x, n - 1 - y),
+        _ => (n - 1 - y, x),
+    }
+}
+
+impl Grid {
+    // 顺时针旋转 90°：(x, y) 处的格子移到 (y, n - 1 - x)，颜色与勾选状态随格子一起移动
+    pub fn rotate_90(&self) -> Grid {
+        self.transformed(1)
+    }
+
+    // 左右翻转：(x, y) 处的格子移到 (x, n - 1 - y)
+    pub fn reflect_horizontal(&self) -> Grid {
+        self.transformed(4)
+    }
+
+    fn transformed(&self, k: usize) -> Grid {
+        let n = self.size();
+        let mut cells = self.0.clone();
+        for cell in self.0.iter().flatten() {
+            let (x, y) = dihedral(k, cell.x, cell.y, n);
+            cells[x][y] = Cell { x, y, ..cell.clone() };
+        }
+        Grid(cells)
+    }
+}
+
+// 两个解的勾选图案能否通过旋转或翻转互相重合（只比较勾选状态，不比较颜色）
+pub fn grids_are_symmetric(a: &Grid, b: &Grid) -> bool {
+    let n = a.size();
+    if b.size() != n {
+        return false;
+    }
+    (0..8).any(|k| {
+        a.0.iter().flatten().all(|cell| {
+            let (i, j) = dihedral(k, cell.x, cell.y, n);
+            b.0[i][j].checked == cell.checked
+        })
+    })
+}
+
+// ----------------------------- 规则校验实现 -----------------------------
+impl Grid {
+    // 网格边长，由网格本身的行数决定
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.0.len()
+    }
+
+    // 只看行数，不检查网格是否为方形
+    pub fn config(&self) -> GridConfig {
+        GridConfig { size: self.size() }
+    }
+
+    // (x, y) 所在的两条对角线编号 (diag1, diag2)
+    pub const fn diagonal_index(x: usize, y: usize) -> (usize, usize) {
+        (diag1_index(x, y), diag2_index(x, y))
+    }
+
+    pub fn get_neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let n = self.size();
+        let mut neighbors = Vec::new();
+        for i in x.saturating_sub(1)..=x.saturating_add(1) {
+            for j in y.saturating_sub(1)..=y.saturating_add(1) {
+                if i == x && j == y {
+                    continue;
+                }
+                if i < n && j < n {
+                    neighbors.push((i, j));
+                }
+            }
+        }
+        neighbors
+    }
+
+    pub fn get_four_neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let mut neighbors = Vec::new();
+        // 上
+        if x > 0 {
+            neighbors.push((x - 1, y));
+        }
+        // 下
+        if x + 1 < self.size() {
+            neighbors.push((x + 1, y));
+        }
+        // 左
+        if y > 0 {
+            neighbors.push((x, y - 1));
+        }
+        // 右
+        if y + 1 < self.size() {
+            neighbors.push((x, y + 1));
+        }
+        neighbors
+    }
+
+    // 周围八格中颜色为 color 的格子
+    pub fn neighbors_of_color(&self, x: usize, y: usize, color: Color) -> Vec<(usize, usize)> {
+        self.get_neighbors(x, y)
+            .into_iter()
+            .filter(|(i, j)| self.0[*i][*j].color == color)
+            .collect()
+    }
+
+    // 上下左右四格中颜色为 color 的格子
+    pub fn four_neighbors_of_color(&self, x: usize, y: usize, color: Color) -> Vec<(usize, usize)> {
+        self.get_four_neighbors(x, y)
+            .into_iter()
+            .filter(|(i, j)| self.0[*i][*j].color == color)
+            .collect()
+    }
+
+    pub fn check_red_rule(&self, x: usize, y: usize) -> bool {
+        let neighbors = self.get_neighbors(x, y);
+        let ok = neighbors.iter().any(|(i, j)| self.0[*i][*j].checked);
+        if !ok {
+            debug!("❌ 红格({},{})规则不满足", x, y);
+        }
+        ok
+    }
+
+    // 蓝格规则为"周围勾选的格子不得超过两个"：恰好两个时满足，三个及以上不满足。
+    // 与青格不同，不论蓝格自身是否勾选都生效，蓝格自己也不计入周围的勾选数
+    pub fn check_blue_rule(&self, x: usize, y: usize) -> bool {
+        let neighbors = self.get_neighbors(x, y);
+        let ok = neighbors.iter().filter(|(i, j)| self.0[*i][*j].checked).count() <= 2;
+        if !ok {
+            debug!("❌ 蓝格({},{})规则不满足", x, y);
+        }
+        ok
+    }
+
+    // 绿格自身同时计入所在行和所在列：行列都没有勾选时 0 == 0 成立，
+    // 只有绿格自己被勾选时也是 1 == 1 成立；行里另有勾选而列里没有时才不满足
+    pub fn check_green_rule(&self, x: usize, y: usize) -> bool {
+        let row_count = self.0[x].iter().filter(|cell| cell.checked).count();
+        let col_count = (0..self.size()).filter(|i| self.0[*i][y].checked).count();
+        let ok = row_count == col_count;
+        if !ok {
+            debug!("❌ 绿格({},{})规则不满足", x, y);
+        }
+        ok
+    }
+
+    pub fn check_yellow_rule(&self, x: usize, y: usize) -> bool {
+        self.check_yellow_rule_with(x, y, &self.diagonal_counts())
+    }
+
+    pub fn check_yellow_rule_with(&self, x: usize, y: usize, counts: &DiagonalCounts) -> bool {
+        let count1 = counts.diag1[diag1_index(x, y)];
+        let count2 = counts.diag2[diag2_index(x, y)];
+
+        let ok = count1 == count2;
+        if !ok {
+            debug!("❌ 黄格({},{})规则不满足：对角1勾数={} 对角2勾数={}", x, y, count1, count2);
+        }
+        ok
+    }
+
+    // 一次遍历统计所有对角线的勾选数，同一网格状态下的黄格、碧格校验共用
+    pub fn diagonal_counts(&self) -> DiagonalCounts {
+        let mut counts = DiagonalCounts { diag1: [0; DIAGONALS], diag2: [0; DIAGONALS] };
+        for cell in self.0.iter().flatten().filter(|cell| cell.checked) {
+            counts.diag1[diag1_index(cell.x, cell.y)] += 1;
+            counts.diag2[diag2_index(cell.x, cell.y)] += 1;
+        }
+        counts
+    }
+
+    pub fn get_diagonal(&self, x: usize, y: usize, dir1: (i32, i32), dir2: (i32, i32)) -> Vec<(usize, usize)> {
+        let n = self.size() as i32;
+        let mut cells = Vec::new();
+        let x = x as i32;
+        let y = y as i32;
+
+        // 向dir1方向延伸
+        let (mut cx, mut cy) = (x, y);
+        loop {
+            if cx < 0 || cy < 0 || cx >= n || cy >= n { break; }
+            cells.push((cx as usize, cy as usize));
+            cx += dir1.0;
+            cy += dir1.1;
+        }
+
+        // 向dir2方向延伸（跳过中心点，中心点已在上面加入）
+        let (mut cx, mut cy) = (x + dir2.0, y + dir2.1);
+        loop {
+            if cx < 0 || cy < 0 || cx >= n || cy >= n { break; }
+            cells.push((cx as usize, cy as usize));
+            cx += dir2.0;
+            cy += dir2.1;
+        }
+
+        cells
+    }
+
+    pub fn check_purple_rule(&self, x: usize, y: usize) -> bool {
+        let neighbors = self.get_neighbors(x, y);
+        let ok = neighbors.iter().filter(|(i, j)| self.0[*i][*j].checked).count() % 2 == 1;
+        if !ok {
+            debug!("❌ 紫格({},{})规则不满足", x, y);
+        }
+        ok
+    }
+
+    // 橙格规则与红、蓝、紫格一样，不论橙格自身是否勾选都生效（规则说明为"橙格周围勾选的格子数量须为偶数"）。
+    // 只有青格的规则以自身被勾选为前提，因此这里不对 cell.checked 做判断。
+    pub fn check_orange_rule(&self, x: usize, y: usize) -> bool {
+        let neighbors = self.get_neighbors(x, y);
+        let count = neighbors.iter().filter(|(i, j)| self.0[*i][*j].checked).count();
+        let ok = count % 2 == 0;
+        if !ok {
+            debug!("❌ 橙格({},{})规则不满足：周围勾选数{}不是偶数", x, y, count);
+        }
+        ok
+    }
+
+    // 青格规则是蕴含式"勾选 ⇒ 上下左右至少一个勾选"：未勾选的青格无论邻居如何都满足，这是规则本身的含义，不是漏检。
+    // 求解器中 BitGrid::check_cell_rule 与之一致，rule_mask 包含青格自身，
+    // 因此回溯时要等青格和四个邻居都确定后才判定，未勾选的青格不会被提前剪掉
+    pub fn check_cyan_rule(&self, x: usize, y: usize) -> bool {
+        let cell = &self.0[x][y];
+        if !cell.checked {
+            return true;
+        }
+        let neighbors = self.get_four_neighbors(x, y);
+        let has_checked = neighbors.iter().any(|(i, j)| self.0[*i][*j].checked);
+        if !has_checked {
+            debug!("❌ 青格({},{})勾选时周围上下左右无勾选格子", x, y);
+        }
+        has_checked
+    }
+
+    // 粉格是"孤岛"：周围八格都不能勾选，粉格自身勾不勾都可以
+    pub fn check_pink_rule(&self, x: usize, y: usize) -> bool {
+        let neighbors = self.get_neighbors(x, y);
+        let ok = !neighbors.iter().any(|(i, j)| self.0[*i][*j].checked);
+        if !ok {
+            debug!("❌ 粉格({},{})周围有勾选的格子", x, y);
+        }
+        ok
+    }
+
+    // 灰格所在行（包括灰格自己）勾选的格子严格多于未勾选的格子，5 × 5 时即至少勾 3 个
+    pub fn check_gray_rule(&self, x: usize, y: usize) -> bool {
+        let checked = self.0[x].iter().filter(|cell| cell.checked).count();
+        let ok = checked * 2 > self.size();
+        if !ok {
+            debug!("❌ 灰格({},{})所在行只勾选了 {} 个", x, y, checked);
+        }
+        ok
+    }
+
+    // 灰格规则的列版本（藏青色），与绿格一样把所在列的勾选数牵连进来
+    pub fn check_navy_rule(&self, x: usize, y: usize) -> bool {
+        let checked = (0..self.size()).filter(|i| self.0[*i][y].checked).count();
+        let ok = checked * 2 > self.size();
+        if !ok {
+            debug!("❌ 藏格({},{})所在列只勾选了 {} 个", x, y, checked);
+        }
+        ok
+    }
+
+    // 网格的四个角，按行优先顺序
+    pub fn corners(&self) -> [(usize, usize); 4] {
+        let last = self.size() - 1;
+        [(0, 0), (0, last), (last, 0), (last, last)]
+    }
+
+    // 与青格一样以自身被勾选为前提；栗格本身在角上时，勾选它就已满足
+    pub fn check_maroon_rule(&self, x: usize, y: usize) -> bool {
+        if !self.0[x][y].checked {
+            return true;
+        }
+        let ok = self.corners().iter().any(|(i, j)| self.0[*i][*j].checked);
+        if !ok {
+            debug!("❌ 栗格({},{})勾选时四个角都没有勾选", x, y);
+        }
+        ok
+    }
+
+    // 左上至右下方向、经过(x,y)的整条对角线（即满足 row - col == x - y 的所有格子）
+    pub fn get_main_diagonal(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        let n = self.size();
+        let offset = x as i32 - y as i32;
+        (0..n)
+            .filter_map(|i| {
+                let j = i as i32 - offset;
+                if (0..n as i32).contains(&j) { Some((i, j as usize)) } else { None }
+            })
+            .collect()
+    }
+
+    pub fn check_teal_rule(&self, x: usize, y: usize) -> bool {
+        self.check_teal_rule_with(x, y, &self.diagonal_counts())
+    }
+
+    pub fn check_teal_rule_with(&self, x: usize, y: usize, counts: &DiagonalCounts) -> bool {
+        let count = counts.diag1[diag1_index(x, y)];
+        let ok = count.is_multiple_of(2);
+        if !ok {
+            debug!("❌ 碧格({},{})规则不满足：对角线勾选数{}不是偶数", x, y, count);
+        }
+        ok
+    }
+
+    pub fn check_cell_rule(&self, x: usize, y: usize) -> bool {
+        self.check_cell_rule_with(x, y, None)
+    }
+
+    // counts 为预先统计好的对角线勾选数，为 None 时黄格、碧格各自统计
+    fn check_cell_rule_with(&self, x: usize, y: usize, counts: Option<&DiagonalCounts>) -> bool {
+        let color = self.0[x][y].color;
+        profile_rule(color, || match (color, counts) {
+            (Color::Red, _) => self.check_red_rule(x, y),
+            (Color::Blue, _) => self.check_blue_rule(x, y),
+            (Color::Green, _) => self.check_green_rule(x, y),
+            (Color::Yellow, Some(counts)) => self.check_yellow_rule_with(x, y, counts),
+            (Color::Yellow, None) => self.check_yellow_rule(x, y),
+            (Color::Purple, _) => self.check_purple_rule(x, y),
+            (Color::Orange, _) => self.check_orange_rule(x, y),
+            (Color::Cyan, _) => self.check_cyan_rule(x, y),
+            (Color::Teal, Some(counts)) => self.check_teal_rule_with(x, y, counts),
+            (Color::Teal, None) => self.check_teal_rule(x, y),
+            (Color::Pink, _) => self.check_pink_rule(x, y),
+            (Color::Gray, _) => self.check_gray_rule(x, y),
+            (Color::Maroon, _) => self.check_maroon_rule(x, y),
+            (Color::Navy, _) => self.check_navy_rule(x, y),
+            _ => true,
+        })
+    }
+
+    // 按颜色分组的格子坐标
+    pub fn cells_by_color(&self) -> HashMap<Color, Vec<(usize, usize)>> {
+        let mut groups: HashMap<Color, Vec<(usize, usize)>> = HashMap::new();
+        for cell in self.0.iter().flatten() {
+            groups.entry(cell.color).or_default().push((cell.x, cell.y));
+        }
+        groups
+    }
+
+    // 校验某一颜色的全部格子
+    pub fn check_all_of_color(&self, color: Color) -> bool {
+        self.cells_by_color()
+            .get(&color)
+            .is_none_or(|cells| cells.iter().all(|(i, j)| self.check_cell_rule(*i, *j)))
+    }
+
+    // 逐格校验全部规则，不短路，返回所有不满足的格子（按 Color::ALL 的颜色顺序、同色内按行优先顺序）
+    pub fn check_all_rules(&self) -> Result<(), Vec<RuleViolation>> {
+        let groups = self.ordered_color_groups();
+        let counts = self.diagonal_counts();
+        let violations: Vec<RuleViolation> = groups
+            .iter()
+            .flat_map(|(_, cells)| cells.iter())
+            .filter_map(|&(x, y)| self.rule_violation(x, y, Some(&counts)))
+            .collect();
+        if violations.is_empty() { Ok(()) } else { Err(violations) }
+    }
+
+    // 对外的校验入口，与 check_all_rules 相同；五连和总勾选数另由 check_k_in_a_row、check_total_checked 判断
+    pub fn validate(&self) -> Result<(), Vec<RuleViolation>> {
+        self.check_all_rules()
+    }
+
+    // (x, y) 的规则不满足时给出原因，满足或无规则时返回 None
+    fn rule_violation(&self, x: usize, y: usize, counts: Option<&DiagonalCounts>) -> Option<RuleViolation> {
+        if self.check_cell_rule_with(x, y, counts) {
+            return None;
+        }
+        let neighbors_checked = || self.get_neighbors(x, y).iter().filter(|(i, j)| self.0[*i][*j].checked).count();
+        let row_checked = self.0[x].iter().filter(|cell| cell.checked).count();
+        let owned;
+        let counts = match counts {
+            Some(counts) => counts,
+            None => {
+                owned = self.diagonal_counts();
+                &owned
+            }
+        };
+        let color = self.0[x][y].color;
+        let reason = match color {
+            Color::Red => "周围八格都没有勾选".to_string(),
+            Color::Blue => format!("周围勾选了 {} 个格子，超过两个", neighbors_checked()),
+            Color::Green => {
+                let col_checked = (0..self.size()).filter(|i| self.0[*i][y].checked).count();
+                format!("所在行勾选 {} 个，所在列勾选 {} 个", row_checked, col_checked)
+            }
+            Color::Yellow => format!(
+                "两条对角线分别勾选 {} 个和 {} 个",
+                counts.diag1[diag1_index(x, y)],
+                counts.diag2[diag2_index(x, y)]
+            ),
+            Color::Purple => format!("周围勾选了 {} 个格子，不是奇数", neighbors_checked()),
+            Color::Orange => format!("周围勾选了 {} 个格子，不是偶数", neighbors_checked()),
+            Color::Cyan => "已勾选，但上下左右都没有勾选的格子".to_string(),
+            Color::Teal => format!("所在对角线勾选了 {} 个格子，不是偶数", counts.diag1[diag1_index(x, y)]),
+            Color::Pink => format!("周围勾选了 {} 个格子", neighbors_checked()),
+            Color::Gray => format!("所在行勾选 {} 个，未勾选 {} 个", row_checked, self.size() - row_checked),
+            Color::Maroon => "已勾选，但四个角都没有勾选".to_string(),
+            Color::Navy => {
+                let col_checked = (0..self.size()).filter(|i| self.0[*i][y].checked).count();
+                format!("所在列勾选 {} 个，未勾选 {} 个", col_checked, self.size() - col_checked)
+            }
+            Color::White | Color::Black => unreachable!("白格、黑格没有规则"),
+        };
+        Some(RuleViolation { x, y, color, reason })
+    }
+
+    // 按 Color::ALL 的顺序排列的分组，便于调用方预先算好、在热路径中复用；
+    // 白格、黑格没有规则，不参与分组校验
+    pub fn ordered_color_groups(&self) -> Vec<(Color, Vec<(usize, usize)>)> {
+        let mut groups = self.cells_by_color();
+        Color::ALL
+            .iter()
+            .filter(|color| !is_rule_free(**color))
+            .filter_map(|color| groups.remove(color).map(|cells| (*color, cells)))
+            .collect()
+    }
+
+    // 按颜色逐组校验，某一颜色不满足时直接短路
+    pub fn check_rules_by_color(&self, groups: &[(Color, Vec<(usize, usize)>)]) -> bool {
+        let counts = groups
+            .iter()
+            .any(|(color, _)| matches!(color, Color::Yellow | Color::Teal))
+            .then(|| self.diagonal_counts());
+        for (color, cells) in groups {
+            if !cells.iter().all(|(i, j)| self.check_cell_rule_with(*i, *j, counts.as_ref())) {
+                debug!("❌ 首个不满足的颜色: {:?}", color);
+                return false;
+            }
+        }
+        true
+    }
+
+    // 规则判定所依赖的全部格子坐标（白格、黑格无规则，返回空）
+    pub fn rule_dependencies(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        match self.0[x][y].color {
+            Color::Red | Color::Blue | Color::Purple | Color::Orange | Color::Pink => self.get_neighbors(x, y),
+            Color::Green => {
+                let n = self.size();
+                let mut cells: Vec<(usize, usize)> = (0..n).map(|j| (x, j)).collect();
+                cells.extend((0..n).filter(|i| *i != x).map(|i| (i, y)));
+                cells
+            }
+            Color::Yellow => {
+                let mut cells = self.get_diagonal(x, y, (-1, -1), (1, 1));
+                cells.extend(self.get_diagonal(x, y, (-1, 1), (1, -1)));
+                cells.sort_unstable();
+                cells.dedup();
+                cells
+            }
+            Color::Cyan => {
+                let mut cells = self.get_four_neighbors(x, y);
+                cells.push((x, y));
+                cells
+            }
+            Color::Teal => self.get_main_diagonal(x, y),
+            Color::Gray => (0..self.size()).map(|j| (x, j)).collect(),
+            Color::Navy => (0..self.size()).map(|i| (i, y)).collect(),
+            Color::Maroon => {
+                let mut cells = self.corners().to_vec();
+                cells.push((x, y));
+                cells.sort_unstable();
+                cells.dedup();
+                cells
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // 勾选过程中的实时校验，不需要知道哪些格子已确定：只报告之后再多勾格子也无法挽回的违反
+    // （蓝格、粉格周围勾多了，见 violation_is_final），其余规则还可能随后续勾选变为满足，暂不评判
+    pub fn final_violations(&self) -> Vec<RuleViolation> {
+        let counts = self.diagonal_counts();
+        self.0
+            .iter()
+            .flatten()
+            .filter(|cell| violation_is_final(cell.color))
+            .filter_map(|cell| self.rule_violation(cell.x, cell.y, Some(&counts)))
+            .collect()
+    }
+
+    pub fn is_valid_so_far(&self) -> bool {
+        self.final_violations().is_empty()
+    }
+
+    // 仅校验依赖格子已全部确定的那些规则，未确定的格子暂不评判
+    pub fn is_valid_partial(&self, decided: &HashSet<(usize, usize)>) -> bool {
+        for i in 0..self.size() {
+            for j in 0..self.size() {
+                if is_rule_free(self.0[i][j].color) {
+                    continue;
+                }
+                let ready = self.rule_dependencies(i, j).iter().all(|pos| decided.contains(pos));
+                if ready && !self.check_cell_rule(i, j) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    // 网格中出现过的颜色，按 Color::ALL 的顺序返回
+    pub fn colors_present(&self) -> Vec<Color> {
+        Color::ALL
+            .iter()
+            .copied()
+            .filter(|color| self.0.iter().flatten().any(|cell| cell.color == *color))
+            .collect()
+    }
+
+    #[inline]
+    pub fn checked_count(&self) -> usize {
+        self.0.iter().flatten().filter(|cell| cell.checked).count()
+    }
+
+    pub fn check_total_checked(&self, max_checked: usize) -> bool {
+        let total = self.checked_count();
+        if total > max_checked {
+            debug!("❌ 总勾选数超过限制: {} > {}", total, max_checked);
+        }
+        total <= max_checked
+    }
+
+    // 所有可能的五连：n 行、n 列，以及 (k, k) 左上至右下、(k, n - 1 - k) 右上至左下两条对角线
+    pub fn iter_lines(&self) -> impl Iterator<Item = Vec<(usize, usize)>> {
+        let n = self.size();
+        let rows = (0..n).map(move |i| (0..n).map(|j| (i, j)).collect());
+        let columns = (0..n).map(move |j| (0..n).map(|i| (i, j)).collect());
+        let diagonals = [
+            (0..n).map(|k| (k, k)).collect(),
+            (0..n).map(|k| (k, n - 1 - k)).collect(),
+        ];
+        rows.chain(columns).chain(diagonals)
+    }
+
+    fn is_line_checked(&self, line: &[(usize, usize)]) -> bool {
+        line.iter().all(|(i, j)| self.0[*i][*j].checked)
+    }
+
+    // 所有长度为 k 的连续格段：每行、每列以及两个方向上长度不小于 k 的对角线，各自按滑动窗口切分。
+    // k 等于边长时与 iter_lines 给出的线完全相同
+    pub fn windows(&self, k: usize) -> impl Iterator<Item = Vec<(usize, usize)>> {
+        let n = self.size();
+        let mut lines: Vec<Vec<(usize, usize)>> = Vec::new();
+        lines.extend((0..n).map(|i| (0..n).map(|j| (i, j)).collect()));
+        lines.extend((0..n).map(|j| (0..n).map(|i| (i, j)).collect()));
+        // 以第 0 行或第 0 列上的格子为起点，分别向右下、左下延伸
+        for start in 0..2 * n - 1 {
+            let (x, y) = if start < n { (0, start) } else { (start - n + 1, 0) };
+            lines.push((0..n - x.max(y)).map(|d| (x + d, y + d)).collect());
+            let (x, y) = if start < n { (0, n - 1 - start) } else { (start - n + 1, n - 1) };
+            lines.push((0..(n - x).min(y + 1)).map(|d| (x + d, y - d)).collect());
+        }
+        lines
+            .into_iter()
+            .filter(move |line| k > 0 && line.len() >= k)
+            .flat_map(move |line| (0..=line.len() - k).map(move |s| line[s..s + k].to_vec()).collect::<Vec<_>>())
+    }
+
+    // 任意行、列或对角线上有连续 k 个格子全部勾选
+    pub fn check_k_in_a_row(&self, k: usize) -> bool {
+        self.windows(k).any(|window| self.is_line_checked(&window))
+    }
+
+    // 返回所有已全部勾选的行、列和对角线
+    pub fn winning_lines(&self) -> Vec<Vec<(usize, usize)>> {
+        self.iter_lines().filter(|line| self.is_line_checked(line)).collect()
+    }
+
+    // 尺寸与 color_grid 相同
+    pub fn new_blank(color_grid: &[Vec<Color>]) -> Self {
+        let n = color_grid.len();
+        Grid(
+            (0..n).map(|i| {
+                (0..n).map(|j| Cell {
+                    x: i, y: j,
+                    color: color_grid[i][j],
+                    checked: false,
+                }).collect()
+            }).collect()
+        )
+    }
+
+    // 勾选状态的紧凑表示：第 x * n + y 位表示 (x, y) 是否勾选（行优先），5 × 5 时只用到低 25 位。
+    // 用 u64 而不是 u32：网格最大为 8 × 8，共 64 个格子，u32 只放得下 5 × 5 及以下的网格。
+    // 与 BitGrid 固定按 8 × 8 排布不同，这里按实际边长紧密排列；颜色布局相同时两个解相等当且仅当指纹相等
+    pub fn solution_fingerprint(&self) -> u64 {
+        let n = self.size();
+        self.0.iter().flatten().filter(|cell| cell.checked).fold(0, |fp, cell| fp | 1 << (cell.x * n + cell.y))
+    }
+
+    // solution_fingerprint 的逆操作，超出 n × n 的高位被忽略
+    pub fn from_fingerprint(color_grid: &[Vec<Color>], fp: u64) -> Grid {
+        let mut grid = Grid::new_blank(color_grid);
+        let n = grid.size();
+        for cell in grid.0.iter_mut().flatten() {
+            cell.checked = fp & 1 << (cell.x * n + cell.y) != 0;
+        }
+        grid
+    }
+
+    // 从每行一串颜色字母（见 Color::to_char）的文本解析出空白网格，如 "RBBGW\nWYCPO\n..."；
+    // 行首尾的空白与空行会被忽略
+    pub fn from_string(s: &str) -> Result<Grid, ParseError> {
+        let rows: Vec<&str> = s.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+        let n = rows.len();
+        if n == 0 || n > MAX_GRID_SIZE {
+            return Err(ParseError::WrongRowCount { found: n });
+        }
+        let mut color_grid = Vec::with_capacity(n);
+        for (row, line) in rows.iter().enumerate() {
+            let colors = line
+                .chars()
+                .enumerate()
+                .map(|(col, c)| Color::from_char(c).ok_or(ParseError::UnknownColorChar { row, col, found: c }))
+                .collect::<Result<Vec<Color>, ParseError>>()?;
+            if colors.len() != n {
+                return Err(ParseError::WrongColCount { row, expected: n, found: colors.len() });
+            }
+            color_grid.push(colors);
+        }
+        Ok(Grid::new_blank(&color_grid))
+    }
+
+    // from_string 的逆操作，只记录颜色、不记录勾选状态
+    pub fn to_string_compact(&self) -> String {
+        self.0
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.color.to_char()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // 表格软件可直接打开的 CSV：每行一排格子，每个单元格为 "颜色英文名,是否勾选"，如 "Red,true"，
+    // 单元格内含逗号，因此总是加双引号
+    pub fn to_csv(&self) -> String {
+        self.0
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| format!("\"{:?},{}\"", cell.color, cell.checked))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    // to_csv 的逆操作；也接受表格软件另存的 CSV：颜色名不区分大小写，勾选状态不区分大小写，
+    // 单元格两侧的空白与空行会被忽略
+    pub fn from_csv(s: &str) -> Result<Grid, CsvParseError> {
+        let rows = s
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(row, line)| split_csv_line(line).ok_or(CsvParseError::UnterminatedQuote { row }))
+            .collect::<Result<Vec<Vec<String>>, CsvParseError>>()?;
+        let n = rows.len();
+        if n == 0 || n > MAX_GRID_SIZE {
+            return Err(CsvParseError::WrongRowCount { found: n });
+        }
+
+        let mut grid = Grid(Vec::with_capacity(n));
+        for (row, fields) in rows.iter().enumerate() {
+            if fields.len() != n {
+                return Err(CsvParseError::WrongColCount { row, expected: n, found: fields.len() });
+            }
+            let mut cells = Vec::with_capacity(n);
+            for (col, field) in fields.iter().enumerate() {
+                let (name, checked) = field
+                    .split_once(',')
+                    .ok_or_else(|| CsvParseError::MalformedCell { row, col, found: field.clone() })?;
+                let color = Color::from_name(name.trim())
+                    .ok_or_else(|| CsvParseError::UnknownColor { row, col, found: name.trim().to_string() })?;
+                let checked = match checked.trim().to_ascii_lowercase().as_str() {
+                    "true" => true,
+                    "false" => false,
+                    _ => return Err(CsvParseError::InvalidChecked { row, col, found: checked.trim().to_string() }),
+                };
+                cells.push(Cell { x: row, y: col, color, checked });
+            }
+            grid.0.push(cells);
+        }
+        Ok(grid)
+    }
+
+    // 终端里直接可看的表格：用制表符画出边框，每格为颜色字母（见 Color::to_char）加勾选标记（✓ 已勾选，空格未勾选）
+    pub fn to_ascii_art(&self) -> String {
+        let n = self.size();
+        let border = |left: char, mid: char, right: char| {
+            let mut line = String::from(left);
+            line += &vec!["──"; n].join(&mid.to_string());
+            line.push(right);
+            line
+        };
+        let mut lines = vec![border('┌', '┬', '┐')];
+        for (i, row) in self.0.iter().enumerate() {
+            if i > 0 {
+                lines.push(border('├', '┼', '┤'));
+            }
+            let cells: Vec<String> = row
+                .iter()
+                .map(|cell| format!("{}{}", cell.color.to_char(), if cell.checked { '✓' } else { ' ' }))
+                .collect();
+            lines.push(format!("│{}│", cells.join("│")));
+        }
+        lines.push(border('└', '┴', '┘'));
+        lines.join("\n")
+    }
+
+    // 交换两个位置的颜色与勾选状态，x/y 仍与所在位置一致（遗传生成器交叉操作的基础）
+    pub fn swap_cells(&mut self, x1: usize, y1: usize, x2: usize, y2: usize) {
+        let a = self.0[x1][y1].clone();
+        let b = self.0[x2][y2].clone();
+        self.0[x1][y1] = Cell { x: x1, y: y1, ..b };
+        self.0[x2][y2] = Cell { x: x2, y: y2, ..a };
+    }
+
+    // 紧凑格式：{"checked":"1010...","colors":"RRBG..."}，各 size² 个字符（5 × 5 时为 25 个），按行优先排列
+    #[cfg(feature = "serde")]
+    pub fn to_json_compact(&self) -> serde_json::Value {
+        let cells = || self.0.iter().flatten();
+        serde_json::json!({
+            "checked": cells().map(|cell| if cell.checked { '1' } else { '0' }).collect::<String>(),
+            "colors": cells().map(|cell| cell.color.to_char()).collect::<String>(),
+        })
+    }
+
+    // 从紧凑格式还原完整网格；colors 字段存在时须与 color_grid 一致
+    #[cfg(feature = "serde")]
+    pub fn from_json_compact(value: &serde_json::Value, color_grid: &[Vec<Color>]) -> Result<Grid, BingoError> {
+        let checked = value["checked"]
+            .as_str()
+            .ok_or_else(|| BingoError::Format("缺少 checked 字段".to_string()))?;
+        let cells = color_grid.len() * color_grid.len();
+        if checked.len() != cells || !checked.chars().all(|c| c == '0' || c == '1') {
+            return Err(BingoError::Format(format!("checked 应为 {} 位 0/1 字符串: {}", cells, checked)));
+        }
+        if let Some(colors) = value["colors"].as_str() {
+            let expected: String = color_grid.iter().flatten().map(|color| color.to_char()).collect();
+            if colors != expected {
+                return Err(BingoError::Format(format!("colors 与题目布局不一致: {} != {}", colors, expected)));
+            }
+        }
+
+        let mut grid = Grid::new_blank(color_grid);
+        for (cell, c) in grid.0.iter_mut().flatten().zip(checked.chars()) {
+            cell.checked = c == '1';
+        }
+        Ok(grid)
+    }
+
+    // 不考虑任何规则，把每个非黑格以 50% 的概率勾选（黑格保持勾选），用于蒙特卡洛采样
+    pub fn randomize_unchecked(&mut self, rng: &mut impl Rng) {
+        for cell in self.0.iter_mut().flatten() {
+            cell.checked = cell.color == Color::Black || rng.gen_bool(0.5);
+        }
+    }
+}
+
+// 按 CSV 规则拆分一行：逗号分隔，双引号包裹的字段内可以含逗号，"" 表示一个双引号；引号未闭合时返回 None
+fn split_csv_line(line: &str) -> Option<Vec<String>> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', _) => quoted = !quoted,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    fields.push(field);
+    Some(fields)
+}
+
+// ----------------------------- 规则违反报告 -----------------------------
+// check_all_rules 报告的单个格子：坐标、颜色，以及具体哪里不满足
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleViolation {
+    pub x: usize,
+    pub y: usize,
+    pub color: Color,
+    pub reason: String,
+}
+
+impl fmt::Display for RuleViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}格({},{})：{}", self.color.label(), self.x, self.y, self.reason)
+    }
+}
+
+// ----------------------------- 逐步作答 -----------------------------
+// 回放到第 step 步（已执行的步数，从 1 开始）时发现的违反；
+// step 等于步骤总数时也可能是全部勾完后才复核出的违反
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepViolation {
+    pub step: usize,
+    pub violation: RuleViolation,
+}
+
+impl fmt::Display for StepViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "勾完第 {} 步后{}", self.step, self.violation)
+    }
+}
+
+// apply_check_sequence 的错误：步骤来自前端，坐标可能不在网格内
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepError {
+    OutOfBounds { step: usize, x: usize, y: usize, size: usize }, // step 从 1 开始
+    Violation(StepViolation),
+}
+
+impl fmt::Display for StepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StepError::OutOfBounds { step, x, y, size } => {
+                write!(f, "第 {} 步的坐标 ({},{}) 不在 {} × {} 的网格内", step, x, y, size, size)
+            }
+            StepError::Violation(violation) => violation.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for StepError {}
+
+// 只会因为多勾格子而变得更糟的规则：一旦违反，后面再怎么勾都救不回来
+fn violation_is_final(color: Color) -> bool {
+    matches!(color, Color::Blue | Color::Pink)
+}
+
+// 从空白网格（黑格已勾）开始依次勾选 steps 中的格子，供前端的"检查答案"使用。
+// 每一步只复核依赖于刚勾选格子的那些规则，其中无法再挽回的违反（见 violation_is_final）立即报告为这一步的错误；
+// 其余规则要等全部勾完才有定论，最后统一复核。重复勾选同一格视为无操作；坐标不在网格内时返回 OutOfBounds
+pub fn apply_check_sequence(color_grid: &[Vec<Color>], steps: &[(usize, usize)]) -> Result<Grid, StepError> {
+    let mut grid = Grid::new_blank(color_grid);
+    for cell in grid.0.iter_mut().flatten() {
+        cell.checked = cell.color == Color::Black;
+    }
+    let n = grid.size();
+    let rule_cells: Vec<(usize, usize)> = (0..n * n)
+        .map(|i| (i / n, i % n))
+        .filter(|&(i, j)| !is_rule_free(color_grid[i][j]))
+        .collect();
+
+    for (step, &(x, y)) in steps.iter().enumerate() {
+        if x >= n || y >= n {
+            return Err(StepError::OutOfBounds { step: step + 1, x, y, size: n });
+        }
+        grid.0[x][y].checked = true;
+        for &(i, j) in &rule_cells {
+            let affected = (i, j) == (x, y) || grid.rule_dependencies(i, j).contains(&(x, y));
+            if !affected || !violation_is_final(color_grid[i][j]) {
+                continue;
+            }
+            if let Some(violation) = grid.rule_violation(i, j, None) {
+                return Err(StepError::Violation(StepViolation { step: step + 1, violation }));
+            }
+        }
+    }
+
+    match grid.check_all_rules() {
+        Ok(()) => Ok(grid),
+        Err(mut violations) => {
+            Err(StepError::Violation(StepViolation { step: steps.len(), violation: violations.remove(0) }))
+        }
+    }
+}
+
+// ----------------------------- 最少提示 -----------------------------
+impl Grid {
+    // 贪心求一组尽量少的提示格：先把全部勾选格都作为提示，再按行优先顺序逐个尝试去掉，
+    // 只要预先勾上剩下的提示格后题目仍只有这一个解，就保持去掉。结果是极小的（再去掉任何一格都不唯一），不保证最小
+    pub fn minimal_hint_set(&self) -> Result<Vec<(usize, usize)>, BingoError> {
+        let color_grid: Vec<Vec<Color>> = self.0.iter().map(|row| row.iter().map(|cell| cell.color).collect()).collect();
+        let solutions = Solver::builder(color_grid).build()?.solve();
+        Ok(self.minimal_hint_set_among(&solutions))
+    }
+
+    // 同 minimal_hint_set，但只在给定的解集合中判断唯一性，已经求过解时可以省去重复求解。
+    // 若有别的解勾选格严格包含本网格的勾选格，全部勾选格作提示也无法唯一确定，此时原样返回全部勾选格
+    pub fn minimal_hint_set_among(&self, solutions: &[Grid]) -> Vec<(usize, usize)> {
+        let n = self.size();
+        let masks: Vec<u64> = solutions.iter().map(Grid::solution_fingerprint).collect();
+        let is_unique = |hints: u64| masks.iter().filter(|&&mask| mask & hints == hints).count() <= 1;
+
+        let mut hints = self.solution_fingerprint();
+        if !is_unique(hints) {
+            debug!("全部勾选格作提示仍对应多个解，无法精简");
+        } else {
+            for bit in 0..n * n {
+                let candidate = hints & !(1 << bit);
+                if candidate != hints && is_unique(candidate) {
+                    hints = candidate;
+                }
+            }
+        }
+        (0..n * n).filter(|bit| hints & 1 << bit != 0).map(|bit| (bit / n, bit % n)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn colors(layout: &str) -> Vec<Vec<Color>> {
+        let grid = Grid::from_string(layout).unwrap();
+        grid.0.iter().map(|row| row.iter().map(|cell| cell.color).collect()).collect()
+    }
+
+    // 按颜色字母布局建网格，只勾选 checked 中的格子
+    fn grid_with(layout: &str, checked: &[(usize, usize)]) -> Grid {
+        let mut grid = Grid::from_string(layout).unwrap();
+        for &(x, y) in checked {
+            grid.0[x][y].checked = true;
+        }
+        grid
+    }
+
+    // Grid 上的规则与位棋盘版本必须一致
+    fn check(grid: &Grid, x: usize, y: usize) -> bool {
+        let ok = grid.check_cell_rule(x, y);
+        let bits = crate::bitgrid::BitGrid::from_grid(grid);
+        assert_eq!(bits.check_cell_rule(grid.0[x][y].color, x, y, grid.size()), ok, "({},{}) 的位棋盘判定不一致", x, y);
+        ok
+    }
+
+    const NAVY_TOP: &str = "WWNWW\nWWWWW\nWWWWW\nWWWWW\nWWWWW";
+
+    #[test]
+    fn navy_passes_with_a_checked_majority_in_its_column() {
+        let grid = grid_with(NAVY_TOP, &[(1, 2), (3, 2), (4, 2)]);
+        assert!(check(&grid, 0, 2));
+        assert!(grid.check_navy_rule(0, 2));
+    }
+
+    #[test]
+    fn navy_fails_without_a_checked_majority_in_its_column() {
+        // 两个勾选对五格的列不算过半；勾满一整行也不影响列
+        let grid = grid_with(NAVY_TOP, &[(0, 2), (4, 2), (2, 0), (2, 1), (2, 3), (2, 4)]);
+        assert!(!check(&grid, 0, 2));
+        let violation = grid.check_all_rules().unwrap_err().remove(0);
+        assert_eq!((violation.x, violation.y, violation.color), (0, 2, Color::Navy));
+        assert_eq!(violation.reason, "所在列勾选 2 个，未勾选 3 个");
+    }
+
+    #[test]
+    fn navy_letter_and_name_round_trip() {
+        assert_eq!(Color::from_char('N'), Some(Color::Navy));
+        assert_eq!(Color::from_name("navy"), Some(Color::Navy));
+        let grid = Grid::new_blank(&colors(NAVY_TOP));
+        assert_eq!(grid.rule_dependencies(0, 2), vec![(0, 2), (1, 2), (2, 2), (3, 2), (4, 2)]);
+    }
+
+    #[test]
+    fn check_sequence_rejects_out_of_bounds_steps() {
+        let color_grid = colors("WWWWW\nWWWWW\nWWWWW\nWWWWW\nWWWWW");
+        assert_eq!(
+            apply_check_sequence(&color_grid, &[(0, 0), (0, 5)]),
+            Err(StepError::OutOfBounds { step: 2, x: 0, y: 5, size: 5 })
+        );
+        assert!(matches!(
+            apply_check_sequence(&color_grid, &[(usize::MAX, 0)]),
+            Err(StepError::OutOfBounds { step: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn check_sequence_replays_a_valid_answer() {
+        let color_grid = colors("WWWWW\nWWWWW\nWWWWW\nWWWWW\nWWWWW");
+        let steps: Vec<(usize, usize)> = (0..5).map(|y| (2, y)).collect();
+        let grid = apply_check_sequence(&color_grid, &steps).unwrap();
+        assert_eq!(grid.checked_count(), 5);
+    }
+
+    // 橙格在中心 (2,2)，其余都是白格
+    const ORANGE_CENTER: &str = "WWWWW\nWWWWW\nWWOWW\nWWWWW\nWWWWW";
+
+    #[test]
+    fn unchecked_orange_is_still_checked_for_parity() {
+        // 橙格的规则不以自身勾选为前提（与青格、栗格不同），未勾选时周围奇数个勾选同样不满足
+        let grid = grid_with(ORANGE_CENTER, &[(1, 1), (2, 1), (3, 1)]);
+        assert!(!check(&grid, 2, 2));
+        let checked = grid_with(ORANGE_CENTER, &[(2, 2), (1, 1), (2, 1), (3, 1)]);
+        assert!(!check(&checked, 2, 2));
+    }
+
+    #[test]
+    fn orange_passes_with_an_even_neighbor_count_either_way() {
+        assert!(check(&grid_with(ORANGE_CENTER, &[]), 2, 2));
+        assert!(check(&grid_with(ORANGE_CENTER, &[(1, 1), (3, 3)]), 2, 2));
+        assert!(check(&grid_with(ORANGE_CENTER, &[(2, 2), (1, 1), (3, 3)]), 2, 2));
+    }
+
+    #[test]
+    fn teal_counts_its_whole_main_diagonal() {
+        let layout = "WWWWW\nWWWWW\nWWTWW\nWWWWW\nWWWWW";
+        let diagonal = [(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)];
+        for k in 0..=5 {
+            // 对角线之外的勾选（反对角线上的 (0,4)、(4,0)）不影响结果
+            let mut checked = diagonal[..k].to_vec();
+            checked.extend([(0, 4), (4, 0)]);
+            let grid = grid_with(layout, &checked);
+            assert_eq!(check(&grid, 2, 2), k % 2 == 0, "对角线勾选 {} 个", k);
+        }
+        assert_eq!(Grid::new_blank(&colors(layout)).get_main_diagonal(2, 2), diagonal.to_vec());
+    }
+
+    const ALL_WHITE: &str = "WWWWW\nWWWWW\nWWWWW\nWWWWW\nWWWWW";
+
+    #[test]
+    fn five_in_a_row_detects_the_anti_diagonal() {
+        // 从左下 (4,0) 到右上 (0,4)
+        let anti: Vec<(usize, usize)> = (0..5).map(|k| (4 - k, k)).collect();
+        let grid = grid_with(ALL_WHITE, &anti);
+        assert!(grid.check_k_in_a_row(5));
+        assert_eq!(grid.winning_lines().len(), 1);
+        let mut line = grid.winning_lines().remove(0);
+        line.sort_unstable();
+        let mut expected = anti.clone();
+        expected.sort_unstable();
+        assert_eq!(line, expected);
+
+        assert!(!grid_with(ALL_WHITE, &anti[..4]).check_k_in_a_row(5));
+    }
+
+    #[test]
+    fn five_in_a_row_detects_the_main_diagonal() {
+        let main: Vec<(usize, usize)> = (0..5).map(|k| (k, k)).collect();
+        let grid = grid_with(ALL_WHITE, &main);
+        assert!(grid.check_k_in_a_row(5));
+        assert_eq!(grid.winning_lines(), vec![main.clone()]);
+        assert!(!grid_with(ALL_WHITE, &main[1..]).check_k_in_a_row(5));
+    }
+
+    #[test]
+    fn checked_count_of_blank_and_black_only_grids() {
+        let color_grid = colors("KWWWK\nWWWWW\nWWKWW\nWWWWW\nWWWWW");
+        assert_eq!(Grid::new_blank(&color_grid).checked_count(), 0);
+
+        // 求解的初始状态：黑格固定勾选，其余未勾
+        let mut grid = Grid::new_blank(&color_grid);
+        for cell in grid.0.iter_mut().flatten() {
+            cell.checked = cell.color == Color::Black;
+        }
+        assert_eq!(grid.checked_count(), 3);
+
+        let mut grid = Grid::from_string("KKK\nKKK\nKKK").unwrap();
+        grid.0.iter_mut().flatten().for_each(|cell| cell.checked = true);
+        assert_eq!(grid.checked_count(), 9);
+    }
+
+    const GREEN_CENTER: &str = "WWWWW\nWWWWW\nWWGWW\nWWWWW\nWWWWW";
+
+    #[test]
+    fn green_with_empty_row_and_column_passes() {
+        assert!(check(&grid_with(GREEN_CENTER, &[]), 2, 2));
+        // 行列之外的勾选不影响
+        assert!(check(&grid_with(GREEN_CENTER, &[(0, 0), (4, 4)]), 2, 2));
+    }
+
+    #[test]
+    fn lone_checked_green_counts_itself_in_both_lines() {
+        // 绿格自己同时计入行和列（1 == 1），单独勾选绿格是满足的
+        assert!(check(&grid_with(GREEN_CENTER, &[(2, 2)]), 2, 2));
+        // 行里另有勾选、列里只有绿格自己时不满足（2 != 1）
+        assert!(!check(&grid_with(GREEN_CENTER, &[(2, 2), (2, 0)]), 2, 2));
+        // 绿格未勾选、行里有一个勾选而列为空时不满足（1 != 0）
+        let grid = grid_with(GREEN_CENTER, &[(2, 0)]);
+        assert!(!check(&grid, 2, 2));
+        assert_eq!(grid.check_all_rules().unwrap_err()[0].reason, "所在行勾选 1 个，所在列勾选 0 个");
+    }
+
+    #[test]
+    fn neighbors_of_color_filters_by_color() {
+        // 中心四周红蓝相间：四个角为红，上下左右为蓝
+        let grid = Grid::from_string("RBR\nBWB\nRBR").unwrap();
+        assert_eq!(grid.neighbors_of_color(1, 1, Color::Red).len(), 4);
+        assert_eq!(grid.neighbors_of_color(1, 1, Color::Blue).len(), 4);
+        assert!(grid.neighbors_of_color(1, 1, Color::White).is_empty());
+        assert!(grid.four_neighbors_of_color(1, 1, Color::Red).is_empty());
+        assert_eq!(grid.four_neighbors_of_color(1, 1, Color::Blue), vec![(0, 1), (2, 1), (1, 0), (1, 2)]);
+        // 角上的格子只有三个邻居
+        assert_eq!(grid.neighbors_of_color(0, 0, Color::Blue), vec![(0, 1), (1, 0)]);
+        assert_eq!(grid.neighbors_of_color(0, 0, Color::White), vec![(1, 1)]);
+    }
+
+    // 每种规则颜色各一格，另有黑白格，勾选若干格子
+    const MIXED: &str = "RBKGY\nPWOCT\nIAMNW\nWWKWW\nRRBBW";
+
+    #[test]
+    fn swapping_twice_is_the_identity() {
+        let original = grid_with(MIXED, &[(0, 2), (0, 4), (1, 1), (3, 2), (4, 0)]);
+        let mut grid = original.clone();
+        grid.swap_cells(0, 2, 4, 4);
+        assert_ne!(grid, original);
+        assert_eq!((grid.0[0][2].color, grid.0[0][2].checked), (Color::White, false));
+        assert_eq!((grid.0[4][4].x, grid.0[4][4].y, grid.0[4][4].color), (4, 4, Color::Black));
+        grid.swap_cells(0, 2, 4, 4);
+        assert_eq!(grid, original);
+
+        // 与自身交换不改变网格
+        grid.swap_cells(1, 1, 1, 1);
+        assert_eq!(grid, original);
+    }
+
+    #[test]
+    fn swaps_keep_the_colors_present() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut grid = grid_with(MIXED, &[(0, 0), (2, 3)]);
+        let present = grid.colors_present();
+        let mut rng = StdRng::seed_from_u64(227);
+        for _ in 0..200 {
+            let (x1, y1, x2, y2) = (rng.gen_range(0..5), rng.gen_range(0..5), rng.gen_range(0..5), rng.gen_range(0..5));
+            grid.swap_cells(x1, y1, x2, y2);
+            assert_eq!(grid.colors_present(), present);
+        }
+        assert_eq!(grid.checked_count(), 2);
+        assert!(grid.0.iter().enumerate().all(|(x, row)| row.iter().enumerate().all(|(y, cell)| (cell.x, cell.y) == (x, y))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn compact_json_round_trips() {
+        let grid = grid_with(MIXED, &[(0, 2), (0, 4), (1, 1), (3, 2), (4, 0)]);
+        let value = grid.to_json_compact();
+        assert_eq!(value["checked"], "0010101000000000010010000");
+        assert_eq!(value["colors"], "RBKGYPWOCTIAMNWWWKWWRRBBW");
+        assert_eq!(Grid::from_json_compact(&value, &colors(MIXED)).unwrap(), grid);
+
+        // 省略 colors 时只按题目布局还原
+        let checked_only = serde_json::json!({ "checked": value["checked"] });
+        assert_eq!(Grid::from_json_compact(&checked_only, &colors(MIXED)).unwrap(), grid);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn compact_json_rejects_malformed_input() {
+        let layout = colors(MIXED);
+        let bad = [
+            serde_json::json!({ "colors": "RBKGYPWOCTIAMNWWWKWWRRBBW" }),
+            serde_json::json!({ "checked": "001" }),
+            serde_json::json!({ "checked": "0010101000000000010010002" }),
+            serde_json::json!({ "checked": "0010101000000000010010000", "colors": "WBKGYPWOCTIAMNWWWKWWRRBBW" }),
+        ];
+        for value in &bad {
+            assert!(matches!(Grid::from_json_compact(value, &layout), Err(BingoError::Format(_))), "{} 应被拒绝", value);
+        }
+    }
+
+    const BLUE_CENTER: &str = "WWW\nWBW\nWWW";
+
+    #[test]
+    fn blue_passes_with_at_most_two_checked_neighbors() {
+        let neighbors = [(0, 0), (0, 1), (0, 2), (1, 0), (1, 2), (2, 0), (2, 1), (2, 2)];
+        for count in 0..=2 {
+            let grid = grid_with(BLUE_CENTER, &neighbors[..count]);
+            assert!(check(&grid, 1, 1), "{} 个勾选的邻居应满足", count);
+            // 蓝格自己勾选与否都不计入
+            let grid = grid_with(BLUE_CENTER, &[&neighbors[..count], &[(1, 1)][..]].concat());
+            assert!(check(&grid, 1, 1), "蓝格勾选、{} 个勾选的邻居应满足", count);
+        }
+    }
+
+    #[test]
+    fn blue_fails_with_three_or_more_checked_neighbors() {
+        let neighbors = [(0, 0), (0, 1), (0, 2), (1, 0), (1, 2), (2, 0), (2, 1), (2, 2)];
+        for count in [3, 8] {
+            let grid = grid_with(BLUE_CENTER, &[&neighbors[..count], &[(1, 1)][..]].concat());
+            assert!(!check(&grid, 1, 1), "{} 个勾选的邻居应不满足", count);
+        }
+        // 与青格不同，未勾选的蓝格同样要校验
+        let grid = grid_with(BLUE_CENTER, &neighbors[..3]);
+        assert!(!check(&grid, 1, 1));
+        assert!(!grid.check_blue_rule(1, 1));
+    }
+
+    const WHITE_3: &str = "WWW\nWWW\nWWW";
+
+    #[test]
+    fn three_in_a_row_on_a_three_by_three_grid() {
+        assert_eq!(grid_with(WHITE_3, &[]).windows(3).count(), 8);
+        for line in [[(1, 0), (1, 1), (1, 2)], [(0, 2), (1, 2), (2, 2)], [(0, 0), (1, 1), (2, 2)], [(0, 2), (1, 1), (2, 0)]] {
+            assert!(grid_with(WHITE_3, &line).check_k_in_a_row(3), "{:?} 应连成一线", line);
+        }
+        assert!(!grid_with(WHITE_3, &[(0, 0), (0, 1), (1, 2)]).check_k_in_a_row(3));
+        assert!(!grid_with(WHITE_3, &[(0, 0), (0, 1), (1, 1), (2, 0)]).check_k_in_a_row(3));
+    }
+
+    #[test]
+    fn four_in_a_row_on_a_five_by_five_grid() {
+        // 每行、每列各 2 段，两个方向上长 5 的对角线各 2 段、长 4 的各 2 条
+        assert_eq!(grid_with(ALL_WHITE, &[]).windows(4).count(), 28);
+        let row = grid_with(ALL_WHITE, &[(3, 1), (3, 2), (3, 3), (3, 4)]);
+        assert!(row.check_k_in_a_row(4));
+        assert!(!row.check_k_in_a_row(5));
+        // 不经过主对角线的短对角线
+        assert!(grid_with(ALL_WHITE, &[(0, 1), (1, 2), (2, 3), (3, 4)]).check_k_in_a_row(4));
+        assert!(grid_with(ALL_WHITE, &[(1, 3), (2, 2), (3, 1), (4, 0)]).check_k_in_a_row(4));
+        // 中间断开的四格不算
+        assert!(!grid_with(ALL_WHITE, &[(0, 0), (1, 0), (3, 0), (4, 0)]).check_k_in_a_row(4));
+    }
+
+    #[test]
+    fn five_in_a_row_on_a_five_by_five_grid() {
+        assert_eq!(grid_with(ALL_WHITE, &[]).windows(5).count(), 12);
+        assert!(grid_with(ALL_WHITE, &[(2, 0), (2, 1), (2, 2), (2, 3), (2, 4)]).check_k_in_a_row(5));
+        assert!(grid_with(ALL_WHITE, &[(0, 3), (1, 3), (2, 3), (3, 3), (4, 3)]).check_k_in_a_row(5));
+        assert!(!grid_with(ALL_WHITE, &[(0, 1), (1, 2), (2, 3), (3, 4)]).check_k_in_a_row(5));
+        assert!(!grid_with(ALL_WHITE, &[(4, 0), (4, 1), (4, 2), (4, 3), (3, 4)]).check_k_in_a_row(5));
+    }
+
+    const PINK_CENTER: &str = "WWWWW\nWWWWW\nWWIWW\nWWWWW\nWWWWW";
+
+    #[test]
+    fn pink_fails_when_a_diagonal_neighbor_is_checked() {
+        let grid = grid_with(PINK_CENTER, &[(1, 1)]);
+        assert!(!check(&grid, 2, 2));
+        let violation = grid.check_all_rules().unwrap_err().remove(0);
+        assert_eq!((violation.x, violation.y, violation.color), (2, 2, Color::Pink));
+        // 粉格自身勾选也不能抵消邻居的勾选
+        assert!(!check(&grid_with(PINK_CENTER, &[(1, 1), (2, 2)]), 2, 2));
+    }
+
+    #[test]
+    fn pink_passes_as_a_lone_checked_cell() {
+        assert!(check(&grid_with(PINK_CENTER, &[]), 2, 2));
+        assert!(check(&grid_with(PINK_CENTER, &[(2, 2)]), 2, 2));
+        // 距离为 2 的格子不算邻居
+        assert!(check(&grid_with(PINK_CENTER, &[(0, 0), (2, 0), (4, 4), (0, 2)]), 2, 2));
+    }
+
+    #[test]
+    fn compact_string_round_trips() {
+        let grid = Grid::from_string(MIXED).unwrap();
+        assert_eq!(grid.to_string_compact(), MIXED);
+        assert_eq!(Grid::from_string(&grid.to_string_compact()).unwrap(), grid);
+        // 首尾空白与空行被忽略
+        assert_eq!(Grid::from_string("\n  RBW \n\nWKW\nWWC\n").unwrap().to_string_compact(), "RBW\nWKW\nWWC");
+    }
+
+    #[test]
+    fn from_string_reports_parse_errors() {
+        assert_eq!(Grid::from_string("RBW\nWXW\nWWC"), Err(ParseError::UnknownColorChar { row: 1, col: 1, found: 'X' }));
+        assert_eq!(Grid::from_string(" \n\n"), Err(ParseError::WrongRowCount { found: 0 }));
+        let too_many = vec!["W".repeat(MAX_GRID_SIZE + 1); MAX_GRID_SIZE + 1].join("\n");
+        assert_eq!(Grid::from_string(&too_many), Err(ParseError::WrongRowCount { found: MAX_GRID_SIZE + 1 }));
+        assert_eq!(Grid::from_string("RBW\nWW\nWWC"), Err(ParseError::WrongColCount { row: 1, expected: 3, found: 2 }));
+        assert_eq!(Grid::from_string("RBWW\nWWWW\nWWCW"), Err(ParseError::WrongColCount { row: 0, expected: 3, found: 4 }));
+    }
+
+    const GRAY_LEFT: &str = "WWWWW\nWWWWW\nAWWWW\nWWWWW\nWWWWW";
+
+    #[test]
+    fn gray_passes_with_a_checked_majority_in_its_row() {
+        // 灰格自己也计入所在行
+        assert!(check(&grid_with(GRAY_LEFT, &[(2, 0), (2, 3), (2, 4)]), 2, 0));
+        assert!(check(&grid_with(GRAY_LEFT, &[(2, 1), (2, 2), (2, 3), (2, 4)]), 2, 0));
+        // 4 × 4 时须勾选 3 个，2 个正好一半不算过半
+        const GRAY_4: &str = "AWWW\nWWWW\nWWWW\nWWWW";
+        assert!(check(&grid_with(GRAY_4, &[(0, 1), (0, 2), (0, 3)]), 0, 0));
+        assert!(!check(&grid_with(GRAY_4, &[(0, 1), (0, 2)]), 0, 0));
+    }
+
+    #[test]
+    fn gray_fails_without_a_checked_majority_in_its_row() {
+        // 勾满灰格所在的列不影响行
+        let grid = grid_with(GRAY_LEFT, &[(2, 0), (2, 4), (0, 0), (1, 0), (3, 0), (4, 0)]);
+        assert!(!check(&grid, 2, 0));
+        let violation = grid.check_all_rules().unwrap_err().remove(0);
+        assert_eq!((violation.x, violation.y, violation.color), (2, 0, Color::Gray));
+        assert!(!check(&grid_with(GRAY_LEFT, &[]), 2, 0));
+    }
+
+    const MAROON_CENTER: &str = "WWWWW\nWWWWW\nWWMWW\nWWWWW\nWWWWW";
+
+    #[test]
+    fn maroon_passes_when_unchecked_or_with_a_checked_corner() {
+        assert!(check(&grid_with(MAROON_CENTER, &[]), 2, 2));
+        // 未勾选的栗格不要求角上有勾选
+        assert!(check(&grid_with(MAROON_CENTER, &[(1, 1), (0, 1)]), 2, 2));
+        for corner in [(0, 0), (0, 4), (4, 0), (4, 4)] {
+            assert!(check(&grid_with(MAROON_CENTER, &[(2, 2), corner]), 2, 2), "角 {:?} 勾选时应满足", corner);
+        }
+        // 栗格本身在角上
+        assert!(check(&grid_with("WWWM\nWWWW\nWWWW\nWWWW", &[(0, 3)]), 0, 3));
+    }
+
+    #[test]
+    fn maroon_fails_when_checked_without_a_checked_corner() {
+        let grid = grid_with(MAROON_CENTER, &[(2, 2), (0, 1), (1, 0), (3, 4), (4, 3)]);
+        assert!(!check(&grid, 2, 2));
+        let violation = grid.check_all_rules().unwrap_err().remove(0);
+        assert_eq!((violation.x, violation.y, violation.color), (2, 2, Color::Maroon));
+    }
+
+    const YELLOW_CENTER: &str = "WWWWW\nWWWWW\nWWYWW\nWWWWW\nWWWWW";
+
+    #[test]
+    fn diagonal_includes_the_center_once() {
+        let grid = grid_with(YELLOW_CENTER, &[]);
+        let diagonal = grid.get_diagonal(2, 2, (-1, -1), (1, 1));
+        assert_eq!(diagonal, vec![(2, 2), (1, 1), (0, 0), (3, 3), (4, 4)]);
+        let anti = grid.get_diagonal(2, 2, (-1, 1), (1, -1));
+        assert_eq!(anti.iter().filter(|&&cell| cell == (2, 2)).count(), 1);
+        assert_eq!(anti.len(), 5);
+        // 角上的格子只有一个方向可以延伸
+        assert_eq!(grid.get_diagonal(0, 4, (-1, -1), (1, 1)), vec![(0, 4)]);
+        assert_eq!(grid.get_diagonal(0, 4, (-1, 1), (1, -1)).len(), 5);
+    }
+
+    #[test]
+    fn checked_yellow_center_counts_itself_once_on_each_diagonal() {
+        // 只勾黄格自己：两条对角线各 1 个
+        let grid = grid_with(YELLOW_CENTER, &[(2, 2)]);
+        assert!(check(&grid, 2, 2));
+        let counts = grid.diagonal_counts();
+        let on = |line: Vec<(usize, usize)>| line.iter().filter(|&&(i, j)| grid.0[i][j].checked).count();
+        assert_eq!(on(grid.get_diagonal(2, 2, (-1, -1), (1, 1))), 1);
+        assert_eq!(on(grid.get_diagonal(2, 2, (-1, 1), (1, -1))), 1);
+        assert_eq!(counts.diag1[diag1_index(2, 2)], 1);
+        assert_eq!(counts.diag2[diag2_index(2, 2)], 1);
+
+        // 再勾一条对角线上的格子就是 2 对 1
+        assert!(!check(&grid_with(YELLOW_CENTER, &[(2, 2), (1, 1)]), 2, 2));
+        assert!(check(&grid_with(YELLOW_CENTER, &[(2, 2), (1, 1), (3, 1)]), 2, 2));
+    }
+
+    // proptest 用的任意值：颜色取 Color::ALL 中的任意一种，格子坐标在最大边长以内，
+    // 网格边长为 1..=MAX_GRID_SIZE，由任意格子组成，坐标改为与所在位置一致
+    impl proptest::arbitrary::Arbitrary for Color {
+        type Parameters = ();
+        type Strategy = proptest::sample::Select<Color>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            proptest::sample::select(Color::ALL.to_vec())
+        }
+    }
+
+    impl proptest::arbitrary::Arbitrary for Cell {
+        type Parameters = ();
+        type Strategy = proptest::strategy::BoxedStrategy<Cell>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            use proptest::prelude::*;
+            (0..MAX_GRID_SIZE, 0..MAX_GRID_SIZE, any::<Color>(), any::<bool>())
+                .prop_map(|(x, y, color, checked)| Cell { x, y, color, checked })
+                .boxed()
+        }
+    }
+
+    impl proptest::arbitrary::Arbitrary for Grid {
+        type Parameters = ();
+        type Strategy = proptest::strategy::BoxedStrategy<Grid>;
+
+        fn arbitrary_with(_: ()) -> Self::Strategy {
+            use proptest::prelude::*;
+            (1..=MAX_GRID_SIZE)
+                .prop_flat_map(|n| proptest::collection::vec(any::<Cell>(), n * n).prop_map(move |cells| (n, cells)))
+                .prop_map(|(n, cells)| {
+                    let mut grid = Grid(Vec::with_capacity(n));
+                    for (x, row) in cells.chunks(n).enumerate() {
+                        grid.0.push(row.iter().enumerate().map(|(y, cell)| Cell { x, y, ..cell.clone() }).collect());
+                    }
+                    grid
+                })
+                .boxed()
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn csv_round_trips(grid in proptest::prelude::any::<Grid>()) {
+            proptest::prop_assert_eq!(Grid::from_csv(&grid.to_csv()).unwrap(), grid);
+        }
+    }
+
+    #[test]
+    fn from_csv_accepts_spreadsheet_variations() {
+        let csv = "\n \"red, TRUE\" , \"Blue,false\"\n\n\"NAVY,True\",\"white,false\"\n";
+        let grid = Grid::from_csv(csv).unwrap();
+        assert_eq!(grid.to_csv(), "\"Red,true\",\"Blue,false\"\n\"Navy,true\",\"White,false\"\n");
+    }
+
+    #[test]
+    fn from_csv_reports_parse_errors() {
+        let cases = [
+            ("", CsvParseError::WrongRowCount { found: 0 }),
+            ("\"Red,true\",\"Red,true\"\n\"Red,true\"", CsvParseError::WrongColCount { row: 1, expected: 2, found: 1 }),
+            ("\"Red,true", CsvParseError::UnterminatedQuote { row: 0 }),
+            ("Red", CsvParseError::MalformedCell { row: 0, col: 0, found: "Red".to_string() }),
+            ("\"Magenta,true\"", CsvParseError::UnknownColor { row: 0, col: 0, found: "Magenta".to_string() }),
+            ("\"Red,yes\"", CsvParseError::InvalidChecked { row: 0, col: 0, found: "yes".to_string() }),
+        ];
+        for (csv, expected) in cases {
+            assert_eq!(Grid::from_csv(csv), Err(expected), "{:?}", csv);
+        }
+        let too_many = ["\"White,false\""; MAX_GRID_SIZE + 1].join(",") + "\n";
+        assert_eq!(Grid::from_csv(&too_many.repeat(MAX_GRID_SIZE + 1)), Err(CsvParseError::WrongRowCount { found: MAX_GRID_SIZE + 1 }));
+    }
+
+    const CYAN_CORNER_AND_CENTER: &str = "CWWWW\nWWWWW\nWWCWW\nWWWWW\nWWWWW";
+
+    #[test]
+    fn unchecked_cyan_always_passes() {
+        // (0,0) 周围没有勾选
+        let grid = grid_with(CYAN_CORNER_AND_CENTER, &[(4, 4)]);
+        assert!(check(&grid, 0, 0));
+        assert!(check(&grid, 2, 2));
+        // (2,2) 上方的邻居勾选
+        let grid = grid_with(CYAN_CORNER_AND_CENTER, &[(1, 2)]);
+        assert!(check(&grid, 2, 2));
+        assert!(grid.check_all_rules().is_ok());
+    }
+
+    #[test]
+    fn checked_cyan_needs_a_checked_orthogonal_neighbor() {
+        assert!(!check(&grid_with(CYAN_CORNER_AND_CENTER, &[(0, 0)]), 0, 0));
+        assert!(check(&grid_with(CYAN_CORNER_AND_CENTER, &[(0, 0), (1, 0)]), 0, 0));
+        // 斜向的邻居不算
+        assert!(!check(&grid_with(CYAN_CORNER_AND_CENTER, &[(2, 2), (1, 1), (3, 3)]), 2, 2));
+        assert!(check(&grid_with(CYAN_CORNER_AND_CENTER, &[(2, 2), (2, 3)]), 2, 2));
+    }
+
+    #[test]
+    fn red_needs_a_checked_neighbor() {
+        let layout = "WWWWW\nWWWWW\nWWRWW\nWWWWW\nWWWWW";
+        assert!(check(&grid_with(layout, &[(3, 1)]), 2, 2));
+        // 红格自己勾选不算，距离为 2 的格子也不算
+        assert!(!check(&grid_with(layout, &[(2, 2), (0, 0), (4, 2)]), 2, 2));
+        // 角上的红格只有三个邻居
+        assert!(check(&grid_with("RWW\nWWW\nWWW", &[(1, 1)]), 0, 0));
+        assert!(!check(&grid_with("RWW\nWWW\nWWW", &[(2, 2)]), 0, 0));
+    }
+
+    #[test]
+    fn purple_needs_an_odd_neighbor_count() {
+        let layout = "WWWWW\nWWWWW\nWWPWW\nWWWWW\nWWWWW";
+        let neighbors = [(1, 1), (1, 2), (1, 3), (2, 1), (2, 3), (3, 1), (3, 2), (3, 3)];
+        for count in 0..=8 {
+            let grid = grid_with(layout, &neighbors[..count]);
+            assert_eq!(check(&grid, 2, 2), count % 2 == 1, "{} 个勾选的邻居", count);
+        }
+        // 紫格自己勾选不计入
+        assert!(!check(&grid_with(layout, &[(2, 2)]), 2, 2));
+    }
+
+    // 每种规则颜色放在 3 × 3 网格中央（栗格同样在中央，角上是否勾选决定结果），
+    // 直接调用各自的 check_*_rule，各给一个满足和一个不满足的勾选方式
+    #[test]
+    fn every_rule_function_has_a_passing_and_a_failing_case() {
+        type Rule = fn(&Grid, usize, usize) -> bool;
+        type Checked<'a> = &'a [(usize, usize)];
+        let cases: [(Color, Rule, Checked, Checked); 12] = [
+            (Color::Red, Grid::check_red_rule, &[(0, 0)], &[(1, 1)]),
+            (Color::Blue, Grid::check_blue_rule, &[(0, 0), (0, 1)], &[(0, 0), (0, 1), (0, 2)]),
+            (Color::Green, Grid::check_green_rule, &[(1, 0), (0, 1)], &[(1, 0)]),
+            (Color::Yellow, Grid::check_yellow_rule, &[(0, 0), (0, 2)], &[(0, 0)]),
+            (Color::Purple, Grid::check_purple_rule, &[(0, 0)], &[(0, 0), (0, 1)]),
+            (Color::Orange, Grid::check_orange_rule, &[(0, 0), (0, 1)], &[(0, 0)]),
+            (Color::Cyan, Grid::check_cyan_rule, &[(1, 1), (0, 1)], &[(1, 1), (0, 0)]),
+            (Color::Teal, Grid::check_teal_rule, &[(0, 0), (2, 2)], &[(0, 0)]),
+            (Color::Pink, Grid::check_pink_rule, &[(1, 1)], &[(2, 2)]),
+            (Color::Gray, Grid::check_gray_rule, &[(1, 0), (1, 2)], &[(1, 0)]),
+            (Color::Navy, Grid::check_navy_rule, &[(0, 1), (2, 1)], &[(0, 1)]),
+            (Color::Maroon, Grid::check_maroon_rule, &[(1, 1), (0, 0)], &[(1, 1), (0, 1)]),
+        ];
+        for (color, rule, passing, failing) in cases {
+            let layout = format!("WWW\nW{}W\nWWW", color.to_char());
+            let grid = grid_with(&layout, passing);
+            assert!(rule(&grid, 1, 1) && check(&grid, 1, 1), "{:?} 在 {:?} 勾选时应满足", color, passing);
+            let grid = grid_with(&layout, failing);
+            assert!(!rule(&grid, 1, 1) && !check(&grid, 1, 1), "{:?} 在 {:?} 勾选时应不满足", color, failing);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    proptest::proptest! {
+        #[test]
+        fn color_json_round_trips(color in proptest::prelude::any::<Color>()) {
+            let json = serde_json::to_string(&color).unwrap();
+            proptest::prop_assert_eq!(json.clone(), format!("\"{:?}\"", color));
+            proptest::prop_assert_eq!(serde_json::from_str::<Color>(&json).unwrap(), color);
+        }
+
+        #[test]
+        fn grid_json_round_trips(grid in proptest::prelude::any::<Grid>()) {
+            let json = serde_json::to_string(&grid).unwrap();
+            proptest::prop_assert_eq!(serde_json::from_str::<Grid>(&json).unwrap(), grid);
+        }
+    }
+
+    #[test]
+    fn arbitrary_colors_cover_every_variant() {
+        use proptest::strategy::{Strategy, ValueTree};
+        let mut runner = proptest::test_runner::TestRunner::deterministic();
+        let strategy = proptest::prelude::any::<Color>();
+        let seen: HashSet<Color> = (0..2000).map(|_| strategy.new_tree(&mut runner).unwrap().current()).collect();
+        assert_eq!(seen.len(), Color::ALL.len());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn fingerprint_round_trips(grid in proptest::prelude::any::<Grid>()) {
+            let layout: Vec<Vec<Color>> = grid.0.iter().map(|row| row.iter().map(|cell| cell.color).collect()).collect();
+            proptest::prop_assert_eq!(Grid::from_fingerprint(&layout, grid.solution_fingerprint()), grid);
+        }
+    }
+
+    #[test]
+    fn distinct_solutions_have_distinct_fingerprints() {
+        // 行优先：第 x * n + y 位对应 (x, y)
+        assert_eq!(grid_with(ALL_WHITE, &[(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)]).solution_fingerprint(), 0b11111);
+        assert_eq!(grid_with(ALL_WHITE, &[(4, 4)]).solution_fingerprint(), 1 << 24);
+        let full_8: Vec<(usize, usize)> = (0..64).map(|k| (k / 8, k % 8)).collect();
+        let layout_8 = ["WWWWWWWW"; 8].join("\n");
+        assert_eq!(grid_with(&layout_8, &full_8).solution_fingerprint(), u64::MAX);
+
+        let fingerprints: HashSet<u64> = (0u32..1 << 9)
+            .map(|mask| {
+                let checked: Vec<(usize, usize)> = (0..9).filter(|k| mask & (1 << k) != 0).map(|k| (k / 3, k % 3)).collect();
+                grid_with(WHITE_3, &checked).solution_fingerprint()
+            })
+            .collect();
+        assert_eq!(fingerprints.len(), 512);
+    }
+
+    #[test]
+    fn valid_so_far_ignores_rules_that_later_checks_can_still_satisfy() {
+        // 红格周围、青格四邻、灰格所在行都还没有勾选，多勾几格就能满足，不算错
+        let layout = "RWCWW\nWWWWW\nAWWWW\nWWBWW\nWWWWI";
+        let grid = grid_with(layout, &[(0, 2)]);
+        assert!(grid.check_all_rules().is_err());
+        assert!(grid.is_valid_so_far());
+        assert!(grid.final_violations().is_empty());
+        // 蓝格周围恰好两个勾选仍然可以
+        assert!(grid_with(layout, &[(2, 1), (2, 3)]).is_valid_so_far());
+    }
+
+    #[test]
+    fn valid_so_far_reports_blue_and_pink_violations() {
+        let layout = "RWCWW\nWWWWW\nAWWWW\nWWBWW\nWWWWI";
+        // 蓝格 (3,2) 周围勾了三个
+        let violations = grid_with(layout, &[(2, 1), (2, 2), (2, 3)]).final_violations();
+        assert_eq!(violations.len(), 1);
+        assert_eq!((violations[0].x, violations[0].y, violations[0].color), (3, 2, Color::Blue));
+        // 粉格 (4,4) 的邻居被勾选
+        let grid = grid_with(layout, &[(3, 3)]);
+        assert!(!grid.is_valid_so_far());
+        assert_eq!(grid.final_violations()[0].color, Color::Pink);
+    }
+
+    #[test]
+    fn unchecked_green_is_checked_on_a_finished_grid() {
+        // 第 0 行勾满构成五连，其余格子都已是最终状态；(2,2) 的绿格本身不勾选
+        let finished = |extra: &[(usize, usize)]| {
+            let mut checked = vec![(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)];
+            checked.extend_from_slice(extra);
+            grid_with(GREEN_CENTER, &checked)
+        };
+        // 所在行勾选 2 个，所在列只有 (0,2) 一个
+        let grid = finished(&[(2, 0), (2, 1)]);
+        assert!(!grid.0[2][2].checked && grid.check_k_in_a_row(5));
+        assert!(!check(&grid, 2, 2));
+        let violations = grid.check_all_rules().unwrap_err();
+        assert_eq!(violations.len(), 1);
+        assert_eq!((violations[0].x, violations[0].y, violations[0].color), (2, 2, Color::Green));
+        // 行列各 1 个时满足
+        assert!(finished(&[(2, 0)]).check_all_rules().is_ok());
+    }
+}
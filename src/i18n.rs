@@ -0,0 +1,121 @@
+use std::str::FromStr;
+
+use crate::grid::{cell_rule_description, Color};
+
+// ----------------------------- 界面语言 -----------------------------
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    Zh,
+    En,
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "zh" => Ok(Language::Zh),
+            "en" => Ok(Language::En),
+            _ => Err(format!("未知的语言 {}，可选: zh, en", s)),
+        }
+    }
+}
+
+// 规则区中与颜色无关的说明文字，lang 为 "en" 时返回英文，其余一律返回中文。
+// 依次为：总要求、分隔线、"周围"的含义、连线的含义
+pub fn rules_text(lang: &str) -> Vec<&'static str> {
+    if lang == "en" {
+        vec![
+            "Every cell's colour rule must hold.",
+            "-----------------------------------",
+            "\"Around\" means the eight surrounding\ncells, not the cell itself.",
+            "A line can be a row, a column or one of\nthe two main diagonals.",
+        ]
+    } else {
+        vec![
+            "每个格子的颜色规则均需满足",
+            "-----------------------------------",
+            "周围指的是一圈八个格子，不包括自己",
+            "五连钩可以是横排竖排，以及两条对角线",
+        ]
+    }
+}
+
+// 按语言给出图片规则区的全部文字，英文每行的长度按规则栏宽度（310 像素、13.5 号字）折好
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Translator {
+    pub language: Language,
+}
+
+impl Translator {
+    pub fn new(language: Language) -> Self {
+        Self { language }
+    }
+
+    fn lang(self) -> &'static str {
+        match self.language {
+            Language::Zh => "zh",
+            Language::En => "en",
+        }
+    }
+
+    // 格子上标注的颜色名：中文为单字（见 Color::label），英文为完整的英文名
+    pub fn color_name(self, color: Color) -> String {
+        match self.language {
+            Language::Zh => color.label().to_string(),
+            Language::En => format!("{:?}", color),
+        }
+    }
+
+    pub fn color_rule(self, color: Color) -> &'static [&'static str] {
+        if self.language == Language::Zh {
+            return cell_rule_description(color);
+        }
+        match color {
+            Color::Red => &["A red cell needs at least one checked", "cell around it."],
+            Color::Blue => &["A blue cell may have at most two", "checked cells around it."],
+            Color::Black => &["Black cells must be checked."],
+            Color::Green => &["A green cell's row must have as many", "checks as its column."],
+            Color::Yellow => &[
+                "The two diagonals crossing a yellow",
+                "cell (running out to the corners) must",
+                "have the same number of checks.",
+            ],
+            Color::Purple => &["A purple cell needs an odd number of", "checked cells around it."],
+            Color::White => &[],
+            Color::Orange => &["An orange cell needs an even number of", "checked cells around it."],
+            Color::Cyan => &[
+                "A checked cyan cell needs a checked",
+                "cell directly above, below, left or",
+                "right of it (diagonals do not count).",
+            ],
+            Color::Teal => &[
+                "The top-left to bottom-right diagonal",
+                "through a teal cell (itself included)",
+                "needs an even number of checks.",
+            ],
+            Color::Pink => &["A pink cell must have no checked cells", "around it."],
+            Color::Gray => &["A gray cell's row needs more checked", "cells than unchecked ones."],
+            Color::Maroon => &["A checked maroon cell needs at least", "one of the four corner cells checked."],
+            Color::Navy => &["A navy cell's column needs more checked", "cells than unchecked ones."],
+        }
+    }
+
+    // 颜色规则之后的几行：总要求、连线目标、补充说明、解的数量
+    pub fn rules_footer(self, size: usize, solutions_count: usize) -> Vec<String> {
+        let fixed = rules_text(self.lang());
+        let goal = match self.language {
+            Language::Zh => {
+                let numerals = ["零", "一", "二", "三", "四", "五", "六", "七", "八"];
+                format!("最终要把{}个勾连起来，加油吧~", numerals[size])
+            }
+            Language::En => format!("Check {} cells in a line. Good luck!", size),
+        };
+        let count = match self.language {
+            Language::Zh => format!("本日题目共有 {} 个解", solutions_count),
+            Language::En => format!("Today's puzzle has {} solution(s).", solutions_count),
+        };
+        vec![fixed[0].to_string(), goal, fixed[1].to_string(), fixed[2].to_string(), fixed[3].to_string(), count]
+    }
+}
@@ -0,0 +1,19 @@
+pub mod grid;
+pub mod bitgrid;
+pub mod solver;
+pub mod render;
+pub mod storage;
+pub mod generator;
+pub mod config;
+pub mod error;
+pub mod archive;
+pub mod profiling;
+pub mod difficulty;
+pub mod share;
+pub mod i18n;
+#[cfg(feature = "serve")]
+pub mod server;
+
+pub use error::{BingoError, CsvParseError, DecodeError, ParseError};
+pub use grid::{Cell, Color, Grid, RuleViolation, StepError, StepViolation};
+pub use solver::Solver;
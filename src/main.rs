@@ -1,621 +1,921 @@
-use serde::{Serialize, Deserialize};
-use image::{ImageBuffer, Rgb};
-use imageproc::drawing::{draw_text_mut, draw_line_segment_mut};
-use rusttype::{Font, Scale};
-use chrono::Local;
-use rand::{seq::SliceRandom, Rng};
-use log::{info, warn, debug};
-use std::{fs, path::Path, time::Instant};
-use chrono::{Utc, DateTime};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use log::{error, info, warn};
+use rand::{rngs::StdRng, SeedableRng};
+use std::{fs, path::PathBuf, time::{Duration, Instant}};
+use chrono::{Utc, DateTime, NaiveDate};
 use chrono_tz::Asia::Shanghai;
 
-// ----------------------------- 数据结构定义 -----------------------------
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
-enum Color {
-    Red,
-    Blue,
-    Black,
-    Green,
-    Yellow,
-    Purple,
-    White,
-    Orange,
-    Cyan,
+use bingo::{BingoError, Color, Grid, Solver};
+use bingo::error::ResultExt;
+use bingo::archive::{append_to_archive, format_size, ArchiveQuery, PuzzleArchive, PuzzleRecord};
+use bingo::difficulty::{classify_difficulty, puzzle_difficulty, DifficultyTier};
+use bingo::config::{Config, GridConfig, CONFIG_FILE, MAX_GRID_SIZE};
+use bingo::share::{grid_from_url_param, grid_to_url_param};
+use bingo::solver::{canonical_solutions, SolverStats, SolverStrategy};
+use bingo::profiling::{format_rule_profile, set_rule_profiling};
+use bingo::i18n::Language;
+use bingo::generator::{
+    date_from_seed, detect_green_contradictions, generate_color_grid_constrained, generate_color_grid_limited,
+    generate_color_grid_nonadjacent, has_completeable_line, format_grid_colors, solutions_with_constrained_win_line,
+    ColorConstraints,
+};
+use bingo::render::{
+    encode_grid_png, encode_solution_animation, encode_solutions_strip, grid_svg, save_grid_image, save_grid_svg, save_solution_animation,
+    encode_grid_png_with_highlight, save_grid_image_with_highlight, save_solutions_strip, CellCache, ColorTheme, RenderConfig,
+};
+use bingo::storage::{
+    date_folder_solved, date_folder_exists, format_solutions_table, load_solutions_json, move_to_date_folder, save_date_tarball,
+    save_solutions_json, solutions_json,
+};
+
+// ----------------------------- 命令行参数 -----------------------------
+const VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"), " (", env!("BUILD_COMMIT"), " ", env!("BUILD_DATE"), ")"
+);
+
+// 不带子命令时等同于 generate，原有的 `bingo --xxx` 用法保持不变
+#[derive(Parser)]
+#[command(about = "每日 Bingo 题目生成器", version = VERSION, args_conflicts_with_subcommands = true)]
+struct Cli {
+    /// 以 JSON 格式打印版本、构建日期与提交哈希后退出
+    #[arg(long)]
+    version_json: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    generate: GenerateArgs,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-struct Cell {
-    x: usize,
-    y: usize,
-    color: Color,
-    checked: bool,
+#[derive(Subcommand)]
+enum Command {
+    /// 生成新题目并写出全部文件（默认）
+    Generate(Box<GenerateArgs>),
+    /// 读取颜色布局 JSON 并求解，按 solutions.json 的格式输出
+    Solve(SolveArgs),
+    /// 从已保存的 solutions.json 重新渲染全部图片，不重新求解
+    Render(RenderArgs),
+    /// 启动 HTTP 服务，以 JSON 和 PNG 提供输出目录中的题目
+    #[cfg(feature = "serve")]
+    Serve(ServeArgs),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct Grid(Vec<Vec<Cell>>);
-
-// ----------------------------- 规则校验实现 -----------------------------
-impl Grid {
-    fn get_neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
-        let mut neighbors = Vec::new();
-        for i in x.saturating_sub(1)..=x.saturating_add(1) {
-            for j in y.saturating_sub(1)..=y.saturating_add(1) {
-                if i == x && j == y {
-                    continue;
-                }
-                if i < 5 && j < 5 {
-                    neighbors.push((i, j));
-                }
-            }
-        }
-        neighbors
-    }
+// 各子命令共用的参数（--seed 只影响颜色布局的生成，属于 generate）
+#[derive(Args)]
+struct CommonArgs {
+    /// 题目日期（YYYY-MM-DD），默认为北京时间当天
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    date: Option<String>,
 
-    fn get_four_neighbors(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
-        let mut neighbors = Vec::new();
-        // 上
-        if x > 0 {
-            neighbors.push((x - 1, y));
-        }
-        // 下
-        if x < 4 {
-            neighbors.push((x + 1, y));
-        }
-        // 左
-        if y > 0 {
-            neighbors.push((x, y - 1));
-        }
-        // 右
-        if y < 4 {
-            neighbors.push((x, y + 1));
-        }
-        neighbors
-    }
+    /// 总勾选数上限，默认取 bingo.toml 中的 max_checked，未配置时按颜色布局自动估算（黑格数 + 凑成一条线还需的格子数 + 格子总数的三分之二）
+    #[arg(long, value_name = "N")]
+    max_checked: Option<usize>,
 
-    fn check_red_rule(&self, x: usize, y: usize) -> bool {
-        let neighbors = self.get_neighbors(x, y);
-        let ok = neighbors.iter().any(|(i, j)| self.0[*i][*j].checked);
-        if !ok {
-            debug!("❌ 红格({},{})规则不满足", x, y);
-        }
-        ok
-    }
+    /// 网格边长，生成时默认为 5；求解、渲染时须与输入一致
+    #[arg(long, alias = "grid-size", value_name = "N", value_parser = clap::value_parser!(u32).range(3..=MAX_GRID_SIZE as i64))]
+    size: Option<u32>,
+}
 
-    fn check_blue_rule(&self, x: usize, y: usize) -> bool {
-        let neighbors = self.get_neighbors(x, y);
-        let ok = neighbors.iter().filter(|(i, j)| self.0[*i][*j].checked).count() <= 2;
-        if !ok {
-            debug!("❌ 蓝格({},{})规则不满足", x, y);
-        }
-        ok
-    }
+#[derive(Args)]
+struct SolveArgs {
+    #[command(flatten)]
+    common: CommonArgs,
 
-    fn check_green_rule(&self, x: usize, y: usize) -> bool {
-        let row_count = self.0[x].iter().filter(|cell| cell.checked).count();
-        let col_count = (0..5).filter(|i| self.0[*i][y].checked).count();
-        let ok = row_count == col_count;
-        if !ok {
-            debug!("❌ 绿格({},{})规则不满足", x, y);
-        }
-        ok
-    }
+    /// 颜色布局：JSON（如 [["Red","Blue",...],...]）或每行一串颜色字母的文本（如 RBKGW）
+    #[arg(long, value_name = "PATH")]
+    input: PathBuf,
 
-    fn check_yellow_rule(&self, x: usize, y: usize) -> bool {
-        let diag1 = self.get_diagonal(x, y, (-1, -1), (1, 1));
-        let diag2 = self.get_diagonal(x, y, (-1, 1), (1, -1));
+    /// 解的输出路径，默认写到标准输出
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
 
-        let count1 = diag1.iter().filter(|&&(i, j)| self.0[i][j].checked).count();
-        let count2 = diag2.iter().filter(|&&(i, j)| self.0[i][j].checked).count();
+    /// 求解策略：backtrack、backtrack-fc、backtrack-ac3、monte-carlo
+    #[arg(long, value_name = "NAME", default_value = "backtrack-fc")]
+    strategy: SolverStrategy,
 
-        let ok = count1 == count2;
-        if !ok {
-            debug!("❌ 黄格({},{})规则不满足：对角1勾数={} 对角2勾数={}", x, y, count1, count2);
-        }
-        ok
-    }
+    /// 互为旋转或翻转的解只保留一个
+    #[arg(long)]
+    deduplicate_symmetric: bool,
 
-    fn get_diagonal(&self, x: usize, y: usize, dir1: (i32, i32), dir2: (i32, i32)) -> Vec<(usize, usize)> {
-        let mut cells = Vec::new();
-        let x = x as i32;
-        let y = y as i32;
-
-        // 向dir1方向延伸
-        let (mut cx, mut cy) = (x as i32, y as i32);
-        loop {
-            if cx < 0 || cy < 0 || cx >= 5 || cy >= 5 { break; }
-            cells.push((cx as usize, cy as usize));
-            cx += dir1.0;
-            cy += dir1.1;
-        }
+    /// 求解最多运行 N 秒，超时后只输出已找到的解
+    #[arg(long, value_name = "N")]
+    timeout_secs: Option<u64>,
+}
 
-        // 向dir2方向延伸（跳过中心点）
-        let (mut cx, mut cy) = (x as i32, y as i32);
-        loop {
-            if cx < 0 || cy < 0 || cx >= 5 || cy >= 5 { break; }
-            cells.push((cx as usize, cy as usize));
-            cx += dir2.0;
-            cy += dir2.1;
-        }
+#[cfg(feature = "serve")]
+#[derive(Args)]
+struct ServeArgs {
+    /// 监听地址
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+    addr: String,
+}
 
-        cells
-    }
+#[derive(Args)]
+struct RenderArgs {
+    #[command(flatten)]
+    common: CommonArgs,
 
-    fn check_purple_rule(&self, x: usize, y: usize) -> bool {
-        let neighbors = self.get_neighbors(x, y);
-        let ok = neighbors.iter().filter(|(i, j)| self.0[*i][*j].checked).count() % 2 == 1;
-        if !ok {
-            debug!("❌ 紫格({},{})规则不满足", x, y);
-        }
-        ok
-    }
+    /// 已保存的 solutions.json（也可以是 .gz）
+    #[arg(long, value_name = "PATH")]
+    input: PathBuf,
 
-    fn check_orange_rule(&self, x: usize, y: usize) -> bool {
-        let neighbors = self.get_neighbors(x, y);
-        let count = neighbors.iter().filter(|(i, j)| self.0[*i][*j].checked).count();
-        let ok = count % 2 == 0;
-        if !ok {
-            debug!("❌ 橙格({},{})规则不满足：周围勾选数{}不是偶数", x, y, count);
-        }
-        ok
-    }
+    /// 图片输出目录
+    #[arg(long, value_name = "DIR")]
+    output_dir: PathBuf,
 
-    fn check_cyan_rule(&self, x: usize, y: usize) -> bool {
-        let cell = &self.0[x][y];
-        if !cell.checked {
-            return true;
-        }
-        let neighbors = self.get_four_neighbors(x, y);
-        let has_checked = neighbors.iter().any(|(i, j)| self.0[*i][*j].checked);
-        if !has_checked {
-            debug!("❌ 青格({},{})勾选时周围上下左右无勾选格子", x, y);
-        }
-        has_checked
-    }
+    /// 规则文字分几栏排列（1 或 2）
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=2))]
+    rule_columns: u32,
 
-    fn check_all_rules(&self) -> bool {
-        for i in 0..5 {
-            for j in 0..5 {
-                let cell = &self.0[i][j];
-                let valid = match cell.color {
-                    Color::Red => self.check_red_rule(i, j),
-                    Color::Blue => self.check_blue_rule(i, j),
-                    Color::Green => self.check_green_rule(i, j),
-                    Color::Yellow => self.check_yellow_rule(i, j),
-                    Color::Purple => self.check_purple_rule(i, j),
-                    Color::Orange => self.check_orange_rule(i, j),
-                    Color::Cyan => self.check_cyan_rule(i, j),
-                    _ => true,
-                };
-                if !valid {
-                    return false;
-                }
-            }
-        }
-        true
-    }
+    /// 规则文字的语言：zh（中文）或 en（英文）
+    #[arg(long, value_name = "LANG", default_value = "zh")]
+    lang: Language,
 
-    fn check_total_checked(&self, max_checked: usize) -> bool {
-        let total = self.0.iter().flatten().filter(|cell| cell.checked).count();
-        if total > max_checked {
-            debug!("❌ 总勾选数超过限制: {} > {}", total, max_checked);
-        }
-        total <= max_checked
-    }
+    /// 单元格样式：padded 为留间距的圆角色块，compact 为铺满格子的直角色块
+    #[arg(long, value_enum, default_value_t = RenderStyle::Padded)]
+    render_style: RenderStyle,
 
-    fn has_five_in_a_row(&self) -> bool {
-        // 检查行
-        for row in &self.0 {
-            for i in 0..=0 {
-                if row[i..i+5].iter().all(|cell| cell.checked) {
-                    return true;
-                }
-            }
-        }
-        // 检查列
-        for j in 0..5 {
-            for i in 0..=0 {
-                if (i..i+5).all(|k| self.0[k][j].checked) {
-                    return true;
-                }
-            }
-        }
-        // 检查对角线
-        for i in 0..=0 {
-            for j in 0..=0 {
-                if (0..5).all(|k| self.0[i + k][j + k].checked) 
-                || (0..5).all(|k| self.0[i + k][4 - j - k].checked) {
-                    return true;
-                }
-            }
-        }
-        false
-    }
+    /// 配色：light（默认）、dark，或者主题 TOML 文件的路径
+    #[arg(long, value_name = "NAME|PATH", default_value = "light")]
+    theme: String,
 
-    fn new_blank(color_grid: &[Vec<Color>]) -> Self {
-        Grid(
-            (0..5).map(|i| {
-                (0..5).map(|j| Cell {
-                    x: i, y: j,
-                    color: color_grid[i][j],
-                    checked: false,
-                }).collect()
-            }).collect()
-        )
-    }
+    /// SVG 中每个格子附带 <title>，浏览器里鼠标悬停即显示该颜色的英文规则
+    #[arg(long)]
+    svg_tooltips: bool,
+
+    /// 解图片（PNG）中给连成五连的格子加上金色边框
+    #[arg(long)]
+    highlight_lines: bool,
+
+    /// 在每个格子中央写上颜色名（语言同 --lang）
+    #[arg(long)]
+    color_labels: bool,
+}
+
+#[derive(Args)]
+struct GenerateArgs {
+    #[command(flatten)]
+    common: CommonArgs,
+
+    /// 随机种子：同一种子总是生成同一道题；形如 20260309 的种子同时把题目日期定为 2026-03-09
+    #[arg(long, value_name = "SEED")]
+    seed: Option<u64>,
+
+    /// 把求解过程中的部分解渲染成 PNG 输出到该目录（调试用）
+    #[arg(long, value_name = "PATH")]
+    debug_solver_dump: Option<PathBuf>,
+
+    /// 配合 --debug-solver-dump 使用，每访问 N 个节点导出一次
+    #[arg(long, value_name = "N", default_value_t = 100_000)]
+    debug_dump_every: u64,
+
+    /// 以 gzip 压缩写入 solutions.json（输出为 solutions.json.gz）
+    #[arg(long)]
+    compress: bool,
+
+    /// 当天的日期文件夹已存在时删除后重新写入（默认报错退出，避免删掉手动放入的文件）
+    #[arg(long)]
+    overwrite: bool,
+
+    /// 当天的日期文件夹里已有 solutions.json 时仍重新生成并覆盖（默认跳过本次运行）
+    #[arg(long)]
+    force: bool,
+
+    /// 打印输出目录（默认 data/，可在 bingo.toml 中用 output_dir 修改）的磁盘占用报告后退出
+    #[arg(long)]
+    disk_usage: bool,
+
+    /// 统计并打印各颜色规则校验的累计耗时
+    #[arg(long)]
+    profile_rules: bool,
+
+    /// 求解后再随机采样 N 次，对比蒙特卡洛估计与精确解数量；使用 monte-carlo 策略时即为采样次数
+    #[arg(long, value_name = "N")]
+    monte_carlo_samples: Option<usize>,
+
+    /// 求解器找到的解（按旋转翻转去重、五连筛选之前）少于 N 个时重新生成；设为 2 以上会排除唯一解的题目
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    min_solutions: usize,
+
+    /// 求解器找到的解多于 M 个时重新生成，想要唯一解时设为 1；调低 --max-checked 通常也会减少解的数量
+    #[arg(long, value_name = "M")]
+    max_solutions: Option<usize>,
+
+    /// 求解策略：backtrack、backtrack-fc、backtrack-ac3、monte-carlo
+    #[arg(long, value_name = "NAME", default_value = "backtrack-fc")]
+    strategy: SolverStrategy,
+
+    /// 用多线程并行回溯（仅 backtrack、backtrack-fc 策略生效）
+    #[arg(long)]
+    parallel: bool,
+
+    /// 每道题目求解最多运行 N 秒，超时后只保留已找到的解（与 --parallel 同时使用时不生效）
+    #[arg(long, value_name = "N")]
+    timeout_secs: Option<u64>,
+
+    /// 互为旋转或翻转的解只保留一个
+    #[arg(long)]
+    deduplicate_symmetric: bool,
+
+    /// 生成同色格子互不上下左右相邻的布局，避免相同的规则互相满足
+    #[arg(long, conflicts_with = "constraints")]
+    non_adjacent: bool,
+
+    /// 按 TOML 文件中的 [min]、[max] 两张表限制每种颜色的格子数（键为颜色英文名，未列出的颜色沿用默认约束）
+    #[arg(long, value_name = "PATH")]
+    constraints: Option<PathBuf>,
+
+    /// 求解分享参数（生成完成后日志中打印的那串字符）对应的题目，而不是随机生成新题目
+    #[arg(long, value_name = "PARAM")]
+    puzzle: Option<String>,
+
+    /// 只接受难度评分（0.0 ~ 1.0）落在 [MIN, MAX] 内的题目，否则重新生成
+    #[arg(long, num_args = 2, value_names = ["MIN", "MAX"], allow_negative_numbers = true)]
+    difficulty_range: Option<Vec<f64>>,
+
+    /// 只接受该难度档位（easy、medium、hard、extreme）的题目，否则重新生成
+    #[arg(long, value_name = "TIER")]
+    target_difficulty: Option<DifficultyTier>,
+
+    /// 生成完成后只保留最近 N 天的历史题目
+    #[arg(long, value_name = "N")]
+    purge_old: Option<usize>,
+
+    /// 生成完成后删除早于该日期（YYYY-MM-DD）的历史题目
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    purge_before: Option<String>,
+
+    /// 额外写出每个解的逐格勾选动画 solution_<i>.gif，每帧间隔 MS 毫秒（最后一帧停留 2 秒）
+    #[arg(long, value_name = "MS")]
+    animation_delay: Option<u32>,
+
+    /// 额外写出纯文本的解列表 solutions_table.txt（勾选坐标、勾选数与难度）
+    #[arg(long)]
+    export_solutions_table: bool,
+
+    /// 不在标准输出打印第一个解的文本表格
+    #[arg(long)]
+    no_ascii: bool,
+
+    /// 额外写出 hints.json：第一个解的一组极小提示格，预先勾上这些格子后题目只剩这一个解
+    #[arg(long)]
+    generate_hints: bool,
+
+    /// 规则文字分几栏排列（1 或 2），颜色较多时分两栏可以降低图片高度
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=2))]
+    rule_columns: u32,
+
+    /// 规则文字的语言：zh（中文）或 en（英文）
+    #[arg(long, value_name = "LANG", default_value = "zh")]
+    lang: Language,
+
+    /// 单元格样式：padded 为留间距的圆角色块，compact 为铺满格子的直角色块
+    #[arg(long, value_enum, default_value_t = RenderStyle::Padded)]
+    render_style: RenderStyle,
+
+    /// 配色：light（默认）、dark，或者主题 TOML 文件的路径
+    #[arg(long, value_name = "NAME|PATH", default_value = "light")]
+    theme: String,
+
+    /// SVG 中每个格子附带 <title>，浏览器里鼠标悬停即显示该颜色的英文规则
+    #[arg(long)]
+    svg_tooltips: bool,
+
+    /// 解图片（PNG）中给连成五连的格子加上金色边框
+    #[arg(long)]
+    highlight_lines: bool,
+
+    /// 在每个格子中央写上颜色名（语言同 --lang）
+    #[arg(long)]
+    color_labels: bool,
+
+    /// 按条件检索历史题目并打印符合条件的日期后退出（条件见 --search-* 参数）
+    #[arg(long)]
+    search: bool,
+
+    /// 检索：解的数量不少于 N
+    #[arg(long, value_name = "N")]
+    search_min_solutions: Option<usize>,
+
+    /// 检索：解的数量不多于 N
+    #[arg(long, value_name = "N")]
+    search_max_solutions: Option<usize>,
+
+    /// 检索：难度档位（easy、medium、hard、extreme，与 --target-difficulty 相同）
+    #[arg(long, value_name = "TIER")]
+    search_difficulty: Option<DifficultyTier>,
+
+    /// 检索：晚于该日期（YYYY-MM-DD，不含当天）
+    #[arg(long, value_name = "DATE")]
+    search_after: Option<NaiveDate>,
+
+    /// 检索：早于该日期（YYYY-MM-DD，不含当天）
+    #[arg(long, value_name = "DATE")]
+    search_before: Option<NaiveDate>,
+
+    /// 检索：题目中出现该颜色（如 red、teal）
+    #[arg(long, value_name = "COLOR", value_parser = parse_color)]
+    search_color: Option<Color>,
+
+    /// 当天产物的输出形式：dir 写入 data/<日期>/ 文件夹，tar.gz 打包为 data/<日期>.tar.gz（data 为默认输出目录）
+    #[arg(long, value_enum, default_value_t = OutputFormat::Dir)]
+    output_format: OutputFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum RenderStyle {
+    Compact,
+    Padded,
+}
+
+// 各子命令共用的图片参数：尺寸与字体来自配置文件，其余来自命令行
+fn render_config(
+    config: &Config,
+    rule_columns: u32,
+    language: Language,
+    style: RenderStyle,
+    theme: &str,
+    tooltips: bool,
+    show_color_labels: bool,
+) -> Result<RenderConfig, BingoError> {
+    let base = match style {
+        RenderStyle::Compact => RenderConfig::compact(),
+        RenderStyle::Padded => RenderConfig::default(),
+    };
+    let theme = match theme {
+        "light" => ColorTheme::default(),
+        "dark" => ColorTheme::dark_theme(),
+        path => ColorTheme::from_toml(path).context(format!("无法读取配色主题 {}", path))?,
+    };
+    Ok(RenderConfig {
+        cell_size: config.cell_size,
+        rule_font_size: config.rule_font_size,
+        margin: config.margin,
+        rule_column_width: config.rule_column_width,
+        font_path: config.font_path.clone(),
+        rule_columns,
+        language,
+        theme,
+        tooltips,
+        show_color_labels,
+        ..base
+    })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Dir,
+    #[value(name = "tar.gz")]
+    TarGz,
+}
+
+fn parse_date(s: &str) -> Result<String, String> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|_| s.to_string())
+        .map_err(|_| format!("日期格式应为 YYYY-MM-DD: {}", s))
 }
 
-// ----------------------------- 求解器实现 -----------------------------
-struct Solver {
-    color_grid: Vec<Vec<Color>>,
-    max_checked: usize,
+fn parse_color(s: &str) -> Result<Color, String> {
+    Color::from_name(s).ok_or_else(|| format!("未知的颜色: {}", s))
 }
 
-impl Solver {
-    fn new(color_grid: Vec<Vec<Color>>, max_checked: usize) -> Self {
-        Self { color_grid, max_checked }
+// ----------------------------- 主函数 -----------------------------
+fn main() {
+    let cli = Cli::parse();
+    if cli.version_json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "build_date": env!("BUILD_DATE"),
+                "commit": env!("BUILD_COMMIT"),
+            })
+        );
+        return;
     }
 
-    fn initialize_grid(&self) -> Grid {
-        Grid(
-            (0..5).map(|i| {
-                (0..5).map(|j| Cell {
-                    x: i,
-                    y: j,
-                    color: self.color_grid[i][j],
-                    checked: self.color_grid[i][j] == Color::Black, // 黑格默认勾选
-                }).collect()
-            }).collect()
-        )
+    // RUST_LOG 优先，未设置时使用配置中的日志级别
+    // 日志级别也来自配置，因此先读配置；读取失败时等日志初始化之后再报告
+    let (config, config_error) = match Config::load(CONFIG_FILE) {
+        Ok(config) => (config, None),
+        Err(e) => (Config::default(), Some(e)),
+    };
+    let mut logger = env_logger::Builder::from_default_env();
+    logger.format_timestamp_millis().format_module_path(false);
+    if std::env::var_os("RUST_LOG").is_none() {
+        logger.filter_level(config.log_level_filter());
+    }
+    logger.init();
+    info!("程序启动");
+    if let Some(e) = config_error {
+        warn!("⚠️ 无法读取 {}，使用默认配置: {}", CONFIG_FILE, e);
     }
 
-    fn next_position(&self, x: usize, y: usize) -> (usize, usize) {
-        if y == 4 { (x + 1, 0) } else { (x, y + 1) }
+    let result = match cli.command {
+        Some(Command::Generate(args)) => generate(*args, &config),
+        Some(Command::Solve(args)) => solve(args, &config),
+        Some(Command::Render(args)) => render(args, &config),
+        #[cfg(feature = "serve")]
+        Some(Command::Serve(args)) => serve(args, &config),
+        None => generate(cli.generate, &config),
+    };
+    // 输入有误、文件读写失败等都在这里报告，以非零状态退出
+    if let Err(e) = result {
+        error!("❌ {}", e);
+        std::process::exit(1);
     }
+}
 
-    fn solve(&self) -> Vec<Grid> {
-        let mut solutions = Vec::new();
-        let mut current_grid = self.initialize_grid();
-        let initial_checked = current_grid.0.iter().flatten().filter(|c| c.checked).count();
-        self.backtrack(0, 0, &mut current_grid, &mut solutions, initial_checked);
-        solutions
+// 命令行指定了 --size 时须与输入的网格一致
+fn check_size(common: &CommonArgs, actual: usize) -> Result<(), BingoError> {
+    match common.size {
+        Some(size) if size as usize != actual => Err(BingoError::InvalidInput(format!(
+            "输入的网格为 {0} × {0}，与 --size {1} 不一致",
+            actual, size
+        ))),
+        _ => Ok(()),
     }
+}
 
-    fn backtrack(&self, x: usize, y: usize, grid: &mut Grid, solutions: &mut Vec<Grid>, current_checked: usize) {
-        if x == 5 {
-            if grid.check_all_rules() 
-                && grid.has_five_in_a_row() 
-                && current_checked <= self.max_checked 
-            {
-                if !solutions.iter().any(|s| s.0 == grid.0) {
-                    info!("🎉 找到有效解！总勾选数: {}", current_checked);
-                    solutions.push(grid.clone());
-                }
-            }
-            return;
-        }
-    
-        let (next_x, next_y) = self.next_position(x, y);
-        
-        if self.color_grid[x][y] == Color::Black {
-            self.backtrack(next_x, next_y, grid, solutions, current_checked);
-        } else {
-            // 尝试勾选该单元格
-            grid.0[x][y].checked = true;
-            let new_checked = current_checked + 1;
-            
-            // 仅保留总勾选数剪枝
-            if new_checked <= self.max_checked {
-                self.backtrack(next_x, next_y, grid, solutions, new_checked);
-            }
-            
-            // 回溯，尝试不勾选
-            grid.0[x][y].checked = false;
-            self.backtrack(next_x, next_y, grid, solutions, current_checked);
+// --difficulty-range 须满足 0.0 <= MIN <= MAX <= 1.0，否则没有题目能落在范围内，生成会无限重试
+fn check_difficulty_range(range: Option<&[f64]>) -> Result<(), BingoError> {
+    match range {
+        Some(&[min, max]) if !(0.0..=1.0).contains(&min) || !(0.0..=1.0).contains(&max) || min > max => {
+            Err(BingoError::InvalidInput(format!("--difficulty-range {} {} 应满足 0.0 <= MIN <= MAX <= 1.0", min, max)))
         }
+        _ => Ok(()),
     }
 }
 
-// ----------------------------- 输出函数 -----------------------------
-fn save_solutions_json(solutions: &[Grid], path: &str) {
-    let data = serde_json::json!({
-        "solutions": solutions,
-        "total_solutions": solutions.len(),
-    });
-    fs::create_dir_all(Path::new(path).parent().unwrap()).unwrap();
-    fs::write(path, data.to_string()).unwrap();
+// --highlight-lines 时解图片中需要高亮的格子：所有五连上的格子，未开启时为空
+fn winning_cells(solution: &Grid, enabled: bool) -> Vec<(usize, usize)> {
+    if enabled { solution.winning_lines().concat() } else { Vec::new() }
 }
 
-fn save_grid_image(grid: &Grid, path: &str, show_checks: bool, date: &str, solutions_count: usize) {
-    // ----------------------------- 参数配置 -----------------------------
-    let cell_size: u32 = 90;        // 单元格尺寸
-    let rule_font_size: f32 = 13.5; // 规则文字字号
-    let line_spacing: u32 = 22;     // 行间距
-    let margin: u32 = 12;           // 全局边距
-    let rule_column_width: u32 = 310; // 规则栏宽度
-
-    // ----------------------------- 颜色定义 -----------------------------
-    let background_color = Rgb([245u8, 245u8, 245u8]); // 浅灰背景
-    let rule_bg_color = Rgb([255u8, 255u8, 255u8]);    // 规则区白色背景
-    let text_color = Rgb([80u8, 80u8, 80u8]);          // 深灰文字
-    let grid_line_color = Rgb([210u8, 210u8, 210u8]);  // 网格线颜色
-    let check_color = Rgb([100u8, 100u8, 100u8]);      // 勾选标记颜色
-
-    // ----------------------------- 布局计算 -----------------------------
-    // 规则文本
-    let solution_count_str = format!("本日题目共有 {} 个解", solutions_count); // 将 format! 结果存储为局部变量
-    let rules = vec![
-        " ",
-        " ",
-        "红格周围至少有一个被勾选的格子。",
-        "蓝格周围勾选的格子不得超过两个。",
-        "绿格所在行的勾选总数",
-        "须等于所在列的勾选总数。",
-        "黄格所在两条交叉对角线",
-        "（从黄格向四角延伸）的勾选总数必须相等。",
-        "紫格周围被勾选的格子数量须为奇数。",
-        "橙格周围勾选的格子数量须为偶数。",
-        "青格如果被勾选，则其上下左右（不包括对角）",
-        "至少有一个被勾选的格子。",
-        "黑格必须勾。",
-        "每个格子的颜色规则均需满足",
-        "最终要把五个勾连起来，加油吧~",
-        "-----------------------------------",
-        "周围指的是一圈八个格子，不包括自己",
-        "五连钩可以是横排竖排，以及两条对角线",
-        &solution_count_str, // 使用局部变量的引用
-    ];
-
-    // 加载字体
-    let font_data: &[u8] = include_bytes!("../fonts/font.ttf");
-    let font = Font::try_from_bytes(font_data).unwrap();
-
-    // ----------------------------- 图像尺寸计算 -----------------------------
-    // 计算规则文本高度
-    let mut text_height = margin;
-    let scale = Scale::uniform(rule_font_size);
-    for line in &rules {
-        let line_count = line.chars().filter(|c| *c == '\n').count() + 1;
-        text_height += line_count as u32 * line_spacing;
-    }
-
-    // 网格区域参数
-    let grid_area_height = 5 * cell_size + margin * 2;
-    let footer_height = 30; // 版权信息区域高度
-    
-    // 总图像尺寸
-    let img_width = rule_column_width + 5 * cell_size + margin * 3;
-    let img_height = text_height.max(grid_area_height) + footer_height;
-
-    // ----------------------------- 绘制图像 -----------------------------
-    let mut img = ImageBuffer::from_pixel(img_width, img_height, background_color);
-
-    // 绘制规则区背景
-    for x in 0..rule_column_width {
-        for y in 0..img_height {
-            img.put_pixel(x, y, rule_bg_color);
-        }
-    }
+// stats.json：最终采用的题目的求解统计，耗时以毫秒计
+fn format_stats_json(stats: &SolverStats) -> String {
+    serde_json::json!({
+        "nodes_visited": stats.nodes_visited,
+        "branches_pruned": stats.branches_pruned,
+        "time_elapsed_ms": stats.time_elapsed.as_secs_f64() * 1000.0,
+    })
+    .to_string()
+}
 
-    // 绘制规则文本
-    let mut y_pos = margin as i32;
-    for line in rules {
-        draw_text_mut(
-            &mut img,
-            text_color,
-            margin as i32 + 10,
-            y_pos,
-            scale,
-            &font,
-            line,
-        );
-        y_pos += line_spacing as i32 * (line.matches('\n').count() as i32 + 1);
+// 当前题目不可用：随机生成时换一道题重试，指定了 --puzzle 时重试也不会有变化，返回错误
+fn reject(fixed_puzzle: bool, reason: &str) -> Result<(), BingoError> {
+    if fixed_puzzle {
+        return Err(BingoError::InvalidInput(format!("--puzzle 指定的题目不可用：{}", reason)));
     }
+    warn!("{}，重新生成题目...", reason);
+    Ok(())
+}
 
-    // 绘制网格区域
-    let grid_start_x = rule_column_width + margin;
-    let grid_start_y = (img_height - grid_area_height) / 2; // 垂直居中
-    for (i, row) in grid.0.iter().enumerate() {
-        for (j, cell) in row.iter().enumerate() {
-            // 单元格颜色
-            let color = match cell.color {
-                Color::Red => [255, 50, 50],
-                Color::Blue => [70, 130, 180],
-                Color::Black => [40, 40, 40],
-                Color::Green => [50, 205, 50],
-                Color::Yellow => [255, 215, 0],
-                Color::Purple => [128, 0, 128],
-                Color::White => [255, 255, 255],
-                Color::Orange => [255, 165, 0],
-                Color::Cyan => [0, 255, 255],
-            };
-
-            // 单元格坐标
-            let x = grid_start_x + j as u32 * cell_size;
-            let y = grid_start_y + i as u32 * cell_size;
-
-            // 绘制单元格背景
-            for dx in 0..cell_size {
-                for dy in 0..cell_size {
-                    img.put_pixel(x + dx, y + dy, Rgb(color));
-                }
-            }
+fn today() -> String {
+    let beijing_time: DateTime<chrono_tz::Tz> = Utc::now().with_timezone(&Shanghai);
+    beijing_time.format("%Y-%m-%d").to_string()
+}
 
-            // 绘制单元格边框
-            for dx in 0..cell_size {
-                img.put_pixel(x + dx, y, grid_line_color); // 上边框
-                img.put_pixel(x + dx, y + cell_size - 1, grid_line_color); // 下边框
-            }
-            for dy in 0..cell_size {
-                img.put_pixel(x, y + dy, grid_line_color); // 左边框
-                img.put_pixel(x + cell_size - 1, y + dy, grid_line_color); // 右边框
-            }
+// ----------------------------- solve 子命令 -----------------------------
+fn solve(args: SolveArgs, config: &Config) -> Result<(), BingoError> {
+    let text = fs::read_to_string(&args.input).context(format!("无法读取颜色布局文件 {}", args.input.display()))?;
+    // 以 [ 开头的按 JSON 读取，否则按每行一串颜色字母的文本读取
+    let color_grid: Vec<Vec<Color>> = if text.trim_start().starts_with('[') {
+        serde_json::from_str(&text).context("颜色布局 JSON 格式错误")?
+    } else {
+        let grid = Grid::from_string(&text)?;
+        grid.0.iter().map(|row| row.iter().map(|cell| cell.color).collect()).collect()
+    };
+    check_size(&args.common, color_grid.len())?;
 
-            // 绘制勾选标记
-            if show_checks && cell.checked {
-                draw_line_segment_mut(
-                    &mut img,
-                    (x as f32 + 10.0, y as f32 + 10.0),
-                    (x as f32 + cell_size as f32 - 10.0, y as f32 + cell_size as f32 - 10.0),
-                    check_color,
-                );
-                draw_line_segment_mut(
-                    &mut img,
-                    (x as f32 + 10.0, y as f32 + cell_size as f32 - 10.0),
-                    (x as f32 + cell_size as f32 - 10.0, y as f32 + 10.0),
-                    check_color,
-                );
+    let mut builder = Solver::builder(color_grid).strategy(args.strategy);
+    if let Some(max_checked) = args.common.max_checked.or(config.max_checked) {
+        builder = builder.max_checked(max_checked);
+    }
+    let solver = builder.build()?;
+    let mut solutions = match args.timeout_secs {
+        Some(secs) => {
+            let (solutions, timed_out) = solver.solve_with_timeout(Duration::from_secs(secs));
+            if timed_out {
+                warn!("⚠️ 求解超过 {} 秒被中止，以下只是部分解", secs);
             }
+            solutions
         }
+        None => solver.solve(),
+    };
+    if args.deduplicate_symmetric {
+        solutions = canonical_solutions(solutions);
     }
+    info!("共找到 {} 个解", solutions.len());
 
-    // ----------------------------- 版权信息 -----------------------------
-    let footer = format!("Generated by BingoSolver @ {}", date);
-    let footer_scale = Scale::uniform(12.0);
-    draw_text_mut(
-        &mut img,
-        text_color,
-        margin as i32 + 10, // 与规则文字左对齐
-        (img_height - footer_height + 8) as i32, // 保持在同一高度
-        footer_scale,
-        &font,
-        &footer
-    );
-
-    img.save(path).unwrap();
+    let json = solutions_json(&solutions);
+    match &args.output {
+        Some(path) => fs::write(path, json).context(format!("无法写入解文件 {}", path.display()))?,
+        None => println!("{}", json),
+    }
+    Ok(())
 }
 
-fn move_to_date_folder(date: &str) {
-    let date_folder = format!("data/{}", date);
-    if Path::new(&date_folder).exists() {
-        fs::remove_dir_all(&date_folder).unwrap();
+// ----------------------------- render 子命令 -----------------------------
+fn render(args: RenderArgs, config: &Config) -> Result<(), BingoError> {
+    let solutions = load_solutions_json(&args.input.to_string_lossy())
+        .context(format!("无法读取解文件 {}", args.input.display()))?;
+    let Some(first) = solutions.first() else {
+        warn!("{} 中没有解，无需渲染", args.input.display());
+        return Ok(());
+    };
+    check_size(&args.common, first.size())?;
+    let color_grid: Vec<Vec<Color>> = first.0.iter().map(|row| row.iter().map(|cell| cell.color).collect()).collect();
+    let solutions: Vec<Grid> = match args.common.max_checked.or(config.max_checked) {
+        Some(max) => solutions.into_iter().filter(|s| s.checked_count() <= max).collect(),
+        None => solutions,
+    };
+    let date = args.common.date.clone().unwrap_or_else(today);
+
+    fs::create_dir_all(&args.output_dir).context(format!("无法创建输出目录 {}", args.output_dir.display()))?;
+    let mut cell_cache = CellCache::new();
+    let render_config =
+        render_config(config, args.rule_columns, args.lang, args.render_style, &args.theme, args.svg_tooltips, args.color_labels)?;
+    let path = |name: &str| args.output_dir.join(name).to_string_lossy().into_owned();
+    let highlight = |solution: &Grid| winning_cells(solution, args.highlight_lines);
+    for (i, solution) in solutions.iter().enumerate() {
+        let png = path(&format!("solution_{}.png", i));
+        save_grid_image_with_highlight(solution, &png, &date, solutions.len(), &mut cell_cache, &render_config, &highlight(solution))
+            .context("无法保存解图片")?;
+        save_grid_svg(solution, &path(&format!("solution_{}.svg", i)), true, &date, solutions.len(), &render_config)
+            .context("无法保存解 SVG")?;
     }
-    fs::create_dir_all(&date_folder).unwrap();
+    let blank = Grid::new_blank(&color_grid);
+    save_grid_image(&blank, &path("blank.png"), false, &date, solutions.len(), &mut cell_cache, &render_config)
+        .context("无法保存空白图片")?;
+    save_grid_svg(&blank, &path("blank.svg"), false, &date, solutions.len(), &render_config).context("无法保存空白 SVG")?;
+    save_solutions_strip(&solutions, &path("solutions_strip.png"), &date, render_config.font_path.as_deref())
+        .context("无法保存解缩略图")?;
+    info!("已渲染 {} 个解的图片至 {}", solutions.len(), args.output_dir.display());
+    Ok(())
+}
 
-    // 需要保留在根目录的文件名
-    let keep_files = vec!["solutions.json", "blank.png"];
+// ----------------------------- serve 子命令 -----------------------------
+// 日期文件夹的查找与 generate 一致：today 按北京时间计算，只识别 dir 输出形式的文件夹
+#[cfg(feature = "serve")]
+fn serve(args: ServeArgs, config: &Config) -> Result<(), BingoError> {
+    let server = bingo::server::PuzzleServer::new(&config.output_dir, today);
+    server.run(&args.addr).context("HTTP 服务启动失败")
+}
 
-    for entry in fs::read_dir("data").unwrap() {
-        let entry = entry.unwrap();
-        let path = entry.path();
+// ----------------------------- generate 子命令 -----------------------------
+fn generate(cli: GenerateArgs, config: &Config) -> Result<(), BingoError> {
+    let mut config = config.clone();
+    let root = config.output_dir.clone();
+    if cli.compress {
+        config.compress_json = true;
+    }
 
-        // 仅处理文件，跳过目录
-        if !path.is_file() {
-            continue;
+    if cli.disk_usage {
+        let usage = PuzzleArchive::open(&root).disk_usage();
+        println!("{}", usage.report());
+        for (date, size) in usage.outliers() {
+            warn!("⚠️ {} 占用 {}，超过中位数的 10 倍", date, format_size(*size));
         }
+        return Ok(());
+    }
 
-        let file_name = entry.file_name();
-        let file_name_str = file_name.to_str().unwrap();
-
-        // 仅移动非保留文件（且不是当前日期的文件夹）
-        if !keep_files.contains(&file_name_str) && file_name_str != date {
-            let new_path = format!("{}/{}", date_folder, file_name_str);
-            fs::rename(&path, new_path).unwrap();
+    if cli.search {
+        let query = ArchiveQuery {
+            min_solutions: cli.search_min_solutions,
+            max_solutions: cli.search_max_solutions,
+            difficulty: cli.search_difficulty,
+            date_after: cli.search_after,
+            date_before: cli.search_before,
+            has_color: cli.search_color,
+        };
+        for date in PuzzleArchive::open(&root).search(query) {
+            println!("{}", date);
         }
+        return Ok(());
     }
-}
-
-// ----------------------------- 主函数 -----------------------------
-fn main() {
-    env_logger::Builder::from_default_env()
-        .format_timestamp_millis()
-        .format_module_path(false)
-        .filter_level(log::LevelFilter::Info)
-        .init();
-    info!("程序启动");
 
-    fs::create_dir_all("data").expect("无法创建data目录");
+    check_difficulty_range(cli.difficulty_range.as_deref())?;
+    fs::create_dir_all(&root).context(format!("无法创建输出目录 {}", root))?;
+    set_rule_profiling(cli.profile_rules);
+    let puzzle = match cli.puzzle.as_deref() {
+        Some(param) => Some(
+            grid_from_url_param(param).map_err(|e| BingoError::InvalidInput(format!("分享参数解析失败: {}", e)))?,
+        ),
+        None => None,
+    };
+    let grid_config = match &puzzle {
+        Some(color_grid) => {
+            check_size(&cli.common, color_grid.len())?;
+            GridConfig::of(color_grid)?
+        }
+        None => GridConfig::new(cli.common.size.unwrap_or(5) as usize)?,
+    };
+    let constraints = match &cli.constraints {
+        Some(path) => {
+            let constraints = ColorConstraints::load(&path.to_string_lossy())
+                .context(format!("无法读取颜色数量约束 {}", path.display()))?;
+            Some(constraints)
+        }
+        None => None,
+    };
+    // 指定种子时所有重试共用同一个 rng，整个生成过程可复现
+    let mut seeded_rng = cli.seed.map(StdRng::seed_from_u64);
+    // --date 优先，其次由种子推出
+    let fixed_date = cli.common.date.clone().or_else(|| cli.seed.and_then(date_from_seed));
+    if let Some(seed) = cli.seed {
+        info!("使用随机种子 {}，题目日期: {}", seed, fixed_date.as_deref().unwrap_or("当天"));
+    }
+    // 同一天重复运行：已经求解过就直接跳过；文件夹存在但不是完整结果时先报错，不必白白求解一遍
+    let overwrite = cli.overwrite || cli.force;
+    if cli.output_format == OutputFormat::Dir {
+        let date = fixed_date.clone().unwrap_or_else(today);
+        if !cli.force && date_folder_solved(&root, &date) {
+            info!("{}/{}/ 中已有求解结果，跳过本次生成（加 --force 重新生成）", root, date);
+            return Ok(());
+        }
+        if !overwrite && date_folder_exists(&root, &date) {
+            return Err(BingoError::DateFolderExists(format!("{}/{}", root, date)));
+        }
+    }
 
-    let (solutions, date, color_grid) = loop {
-        let utc_time = Utc::now();
-        let beijing_time: DateTime<chrono_tz::Tz> = utc_time.with_timezone(&Shanghai);
-        let date = beijing_time.format("%Y-%m-%d").to_string();
+    let (solutions, date, color_grid, difficulty, stats) = loop {
+        let date = fixed_date.clone().unwrap_or_else(today);
         
         // 生成新的颜色网格
-        let color_grid = generate_color_grid();
+        let color_grid = match (&puzzle, &constraints, &mut seeded_rng) {
+            (Some(color_grid), _, _) => color_grid.clone(),
+            (None, Some(constraints), Some(rng)) => generate_color_grid_constrained(&grid_config, constraints, rng)?,
+            (None, Some(constraints), None) => {
+                generate_color_grid_constrained(&grid_config, constraints, &mut rand::thread_rng())?
+            }
+            (None, None, Some(rng)) if cli.non_adjacent => {
+                generate_color_grid_nonadjacent(&grid_config, config.min_white, config.max_black, rng)
+            }
+            (None, None, None) if cli.non_adjacent => {
+                generate_color_grid_nonadjacent(&grid_config, config.min_white, config.max_black, &mut rand::thread_rng())
+            }
+            (None, None, Some(rng)) => generate_color_grid_limited(&grid_config, config.min_white, config.max_black, rng),
+            (None, None, None) => {
+                generate_color_grid_limited(&grid_config, config.min_white, config.max_black, &mut rand::thread_rng())
+            }
+        };
         info!("生成新题目布局:\n{}", format_grid_colors(&color_grid));
+        if detect_green_contradictions(&color_grid) {
+            reject(puzzle.is_some(), "绿格约束互相矛盾")?;
+            continue;
+        }
+        if !has_completeable_line(&color_grid)? {
+            reject(puzzle.is_some(), "没有同时可以全部勾选的一行和一列")?;
+            continue;
+        }
 
-        let solver = Solver::new(color_grid.clone(), 25);
-        let solutions = solver.solve();
+        let mut builder = Solver::builder(color_grid.clone()).grid_config(grid_config).strategy(cli.strategy);
+        if let Some(max_checked) = cli.common.max_checked.or(config.max_checked) {
+            builder = builder.max_checked(max_checked);
+        }
+        if let Some(samples) = cli.monte_carlo_samples {
+            builder = builder.monte_carlo_samples(samples);
+        }
+        if let Some(dir) = &cli.debug_solver_dump {
+            fs::create_dir_all(dir).context(format!("无法创建求解器调试输出目录 {}", dir.display()))?;
+            builder = builder.debug_dump(dir, cli.debug_dump_every);
+        }
+        let mut solver = builder.build()?;
+        // 解的数量范围先用逐个产出的搜索判断，数到边界就停，解很多的题目不必完整求解；
+        // 下限为 1 时与后面的"未找到解"重复，不单独求一遍
+        if cli.min_solutions > 1 && !solver.solution_count_at_least(cli.min_solutions) {
+            reject(puzzle.is_some(), &format!("解少于 {} 个", cli.min_solutions))?;
+            continue;
+        }
+        if let Some(max) = cli.max_solutions {
+            if solver.solution_count_at_least(max + 1) {
+                reject(puzzle.is_some(), &format!("解多于 {} 个", max))?;
+                continue;
+            }
+        }
+        // 约每秒报告一次进度
+        solver.set_progress_interval(20_000_000);
+        let start = Instant::now();
+        let solutions = if cli.parallel {
+            solver.solve_parallel()
+        } else if let Some(secs) = cli.timeout_secs {
+            let (solutions, timed_out) = solver.solve_with_timeout(Duration::from_secs(secs));
+            if timed_out {
+                warn!("⚠️ 求解超过 {} 秒被中止，只找到 {} 个解，解的数量可能不完整", secs, solutions.len());
+            }
+            solutions
+        } else {
+            solver.solve_with_progress(|nodes| {
+                let elapsed = start.elapsed().as_secs_f64().max(1e-9);
+                info!("求解中：已访问 {} 个节点，{:.0} 节点/秒", nodes, nodes as f64 / elapsed);
+            })
+        };
+        let stats = solver.stats();
+        info!(
+            "求解统计：访问 {} 个节点，剪枝 {} 次，耗时 {:.3} 秒",
+            stats.nodes_visited,
+            stats.branches_pruned,
+            stats.time_elapsed.as_secs_f64(),
+        );
+        if let (Some(samples), false) = (cli.monte_carlo_samples, cli.strategy == SolverStrategy::MonteCarlo) {
+            let estimate = solver.sample_monte_carlo(samples, &mut rand::thread_rng());
+            let found_exact = estimate.found.iter().filter(|s| solutions.iter().any(|e| e.0 == s.0)).count();
+            info!(
+                "蒙特卡洛采样 {} 次，命中 {} 次，估计解数 {:.1}（精确解数 {}），采到 {} 个不同的解，其中 {} 个与精确解一致",
+                estimate.samples,
+                estimate.hits,
+                estimate.estimated_solutions,
+                solutions.len(),
+                estimate.found.len(),
+                found_exact,
+            );
+        }
         
-        if !solutions.is_empty() {
-            break (solutions, date, color_grid);
+        let solutions = if cli.deduplicate_symmetric {
+            let total = solutions.len();
+            let kept = canonical_solutions(solutions);
+            info!("按旋转、翻转去重：{} 个解保留 {} 个", total, kept.len());
+            kept
+        } else {
+            solutions
+        };
+        if solutions.is_empty() {
+            reject(puzzle.is_some(), "未找到解")?;
+            continue;
+        }
+
+        let solutions = solutions_with_constrained_win_line(&solutions, &color_grid);
+        if solutions.is_empty() {
+            reject(puzzle.is_some(), "所有解的五连都不含受约束格子")?;
+            continue;
+        }
+
+        let difficulty = puzzle_difficulty(&solutions, &color_grid);
+        if let Some([min, max]) = cli.difficulty_range.as_deref() {
+            if !(*min..=*max).contains(&difficulty) {
+                reject(puzzle.is_some(), &format!("难度评分 {:.3} 不在 [{}, {}] 内", difficulty, min, max))?;
+                continue;
+            }
         }
-        warn!("未找到解，重新生成题目...");
+        let tier = classify_difficulty(difficulty);
+        if let Some(target) = cli.target_difficulty {
+            if tier != target {
+                reject(puzzle.is_some(), &format!("难度档位 {} 不是目标档位 {}", tier, target))?;
+                continue;
+            }
+        }
+        info!("难度评分: {:.3}（{}）", difficulty, tier);
+        info!("分享参数: {}", grid_to_url_param(&color_grid));
+        break (solutions, date, color_grid, difficulty, stats);
     };
 
+    if cli.profile_rules {
+        info!("规则校验耗时: {}", format_rule_profile());
+    }
+
+    // 不打开图片也能在终端、CI 日志里看到题目
+    if !cli.no_ascii {
+        println!("{} 第一个解（共 {} 个）:\n{}", date, solutions.len(), solutions[0].to_ascii_art());
+    }
+
+    let hints_json = cli.generate_hints.then(|| {
+        let hints = solutions[0].minimal_hint_set_among(&solutions);
+        info!("第一个解共勾选 {} 格，精简后需要 {} 个提示格", solutions[0].checked_count(), hints.len());
+        serde_json::json!({ "solution": 0, "hints": hints }).to_string()
+    });
+
+    let stats_json = format_stats_json(&stats);
+
+    // 同一题目的所有图片共用单元格背景缓存
+    let highlight = |solution: &Grid| winning_cells(solution, cli.highlight_lines);
+    let mut cell_cache = CellCache::new();
+    let render_config = render_config(
+        &config, cli.rule_columns, cli.lang, cli.render_style, &cli.theme, cli.svg_tooltips, cli.color_labels,
+    )?;
+
     // 保存到根目录
-    save_solutions_json(&solutions, "data/solutions.json");
+    save_solutions_json(&solutions, &format!("{}/solutions.json", root), config.compress_json).context("无法写入解文件")?;
     save_grid_image(
         &Grid::new_blank(&color_grid), 
-        "data/blank.png", 
+        &format!("{}/blank.png", root),
         false, 
         &date,
-        solutions.len() // 传递解数量
-    );
-
-    // 保存到日期文件夹
-    move_to_date_folder(&date);
-    save_solutions_json(&solutions, &format!("data/{}/solutions.json", date));
-    for (i, solution) in solutions.iter().enumerate() {
+        solutions.len(), // 传递解数量
+        &mut cell_cache,
+        &render_config,
+    ).context("无法保存空白图片")?;
+
+    if cli.output_format == OutputFormat::TarGz {
+        // 打包模式：不整理日期文件夹，所有产物直接写进压缩包
+        let mut files = vec![
+            ("solutions.json".to_string(), solutions_json(&solutions).into_bytes()),
+            ("stats.json".to_string(), stats_json.clone().into_bytes()),
+        ];
+        if let Some(hints) = &hints_json {
+            files.push(("hints.json".to_string(), hints.clone().into_bytes()));
+        }
+        for (i, solution) in solutions.iter().enumerate() {
+            let png = encode_grid_png_with_highlight(solution, &date, solutions.len(), &mut cell_cache, &render_config, &highlight(solution))
+                .context("解图片编码失败")?;
+            files.push((format!("solution_{}.png", i), png));
+            files.push((format!("solution_{}.svg", i), grid_svg(solution, true, &date, solutions.len(), &render_config).into_bytes()));
+            if let Some(delay) = cli.animation_delay {
+                let gif = encode_solution_animation(solution, &date, solutions.len(), delay, &mut cell_cache, &render_config)
+                    .context("解动画编码失败")?;
+                files.push((format!("solution_{}.gif", i), gif));
+            }
+        }
+        let blank = encode_grid_png(&Grid::new_blank(&color_grid), false, &date, solutions.len(), &mut cell_cache, &render_config)
+            .context("空白图片编码失败")?;
+        files.push(("blank.png".to_string(), blank));
+        files.push(("blank.svg".to_string(), grid_svg(&Grid::new_blank(&color_grid), false, &date, solutions.len(), &render_config).into_bytes()));
+        let strip = encode_solutions_strip(&solutions, &date, render_config.font_path.as_deref()).context("解缩略图编码失败")?;
+        files.push(("solutions_strip.png".to_string(), strip));
+        if cli.export_solutions_table {
+            files.push(("solutions_table.txt".to_string(), format_solutions_table(&solutions).into_bytes()));
+        }
+        let path = save_date_tarball(&root, &date, &files).context("无法写入压缩包")?;
+        info!("结果已保存至 {}/ 和 {}", root, path.display());
+    } else {
+        // 保存到日期文件夹
+        move_to_date_folder(&root, &date, overwrite).context("整理日期文件夹失败")?;
+        save_solutions_json(&solutions, &format!("{}/{}/solutions.json", root, date), config.compress_json)
+            .context("无法写入解文件")?;
+        fs::write(format!("{}/{}/stats.json", root, date), &stats_json).context("无法写入求解统计")?;
+        if let Some(hints) = &hints_json {
+            fs::write(format!("{}/{}/hints.json", root, date), hints).context("无法写入提示格")?;
+        }
+        for (i, solution) in solutions.iter().enumerate() {
+            save_grid_image_with_highlight(
+                solution,
+                &format!("{}/{}/solution_{}.png", root, date, i),
+                &date,
+                solutions.len(), // 传递解数量
+                &mut cell_cache,
+                &render_config,
+                &highlight(solution),
+            ).context("无法保存解图片")?;
+            save_grid_svg(solution, &format!("{}/{}/solution_{}.svg", root, date, i), true, &date, solutions.len(), &render_config)
+                .context("无法保存解 SVG")?;
+            if let Some(delay) = cli.animation_delay {
+                save_solution_animation(
+                    solution,
+                    &format!("{}/{}/solution_{}.gif", root, date, i),
+                    &date,
+                    solutions.len(),
+                    delay,
+                    &mut cell_cache,
+                    &render_config,
+                ).context("无法保存解动画")?;
+            }
+        }
         save_grid_image(
-            solution, 
-            &format!("data/{}/solution_{}.png", date, i), 
-            true, 
+            &Grid::new_blank(&color_grid), 
+            &format!("{}/{}/blank.png", root, date), 
+            false, 
             &date,
-            solutions.len() // 传递解数量
-        );
-    }
-    save_grid_image(
-        &Grid::new_blank(&color_grid), 
-        &format!("data/{}/blank.png", date), 
-        false, 
-        &date,
-        solutions.len()
-    );
-
-    info!("结果已保存至 data/ 和 data/{}/ 文件夹", date);
-}
-
-// ----------------------------- 工具函数 -----------------------------
-fn generate_color_grid() -> Vec<Vec<Color>> {
-    let mut rng = rand::thread_rng();
-    let colors = vec![
-        Color::Red, Color::Blue, Color::Black,
-        Color::Green, Color::Yellow, Color::Purple, 
-        Color::White, Color::Orange, Color::Cyan,
-    ];
-    
-    // 生成初始随机网格
-    let mut grid: Vec<Vec<Color>> = (0..5)
-        .map(|_| (0..5).map(|_| *colors.choose(&mut rng).unwrap()).collect())
-        .collect();
-
-    // 强制至少有2个白格
-    let mut white_count = grid.iter().flatten().filter(|c| **c == Color::White).count();
-    while white_count < 2 {
-        let x = rng.gen_range(0..5);
-        let y = rng.gen_range(0..5);
-        if grid[x][y] != Color::White {
-            grid[x][y] = Color::White;
-            white_count += 1;
+            solutions.len(),
+            &mut cell_cache,
+            &render_config,
+        ).context("无法保存空白图片")?;
+        save_grid_svg(&Grid::new_blank(&color_grid), &format!("{}/{}/blank.svg", root, date), false, &date, solutions.len(), &render_config)
+            .context("无法保存空白 SVG")?;
+        save_solutions_strip(&solutions, &format!("{}/{}/solutions_strip.png", root, date), &date, render_config.font_path.as_deref())
+            .context("无法保存解缩略图")?;
+        if cli.export_solutions_table {
+            fs::write(format!("{}/{}/solutions_table.txt", root, date), format_solutions_table(&solutions))
+                .context("无法写入解列表")?;
         }
+
+        info!("结果已保存至 {0}/ 和 {0}/{1}/ 文件夹", root, date);
     }
 
-    grid
-}
+    let mut archive = PuzzleArchive::open(&root);
+    if let Err(e) = archive.update_index(&date, &solutions) {
+        warn!("⚠️ 更新 {}/index.json 失败: {}", root, e);
+    }
+    let record = PuzzleRecord {
+        date: date.clone(),
+        color_grid: color_grid.clone(),
+        solution_count: solutions.len(),
+        difficulty,
+        seed: cli.seed,
+    };
+    let archive_path = format!("{}/archive.json", root);
+    if let Err(e) = append_to_archive(&record, &archive_path) {
+        warn!("⚠️ 写入 {} 失败: {}", archive_path, e);
+    }
 
-fn format_grid_colors(grid: &[Vec<Color>]) -> String {
-    grid.iter().map(|row| {
-        row.iter().map(|color| match color {
-            Color::Red => "红",
-            Color::Blue => "蓝",
-            Color::Black => "黑",
-            Color::Green => "绿",
-            Color::Yellow => "黄",
-            Color::Purple => "紫",
-            Color::White => "白",
-            Color::Orange => "橙",
-            Color::Cyan => "青",
-        }).collect::<Vec<_>>().join(" ")
-    }).collect::<Vec<_>>().join("\n")
+    // 清理历史题目
+    if let Some(keep) = cli.purge_old {
+        let purged = archive.purge_old(keep).context("清理历史题目失败")?;
+        info!("已清理 {} 天的历史题目，保留最近 {} 天", purged, keep);
+    }
+    if let Some(before) = &cli.purge_before {
+        let purged = archive.purge_before(before).context("清理历史题目失败")?;
+        info!("已清理 {} 天早于 {} 的历史题目", purged, before);
+    }
+    Ok(())
 }
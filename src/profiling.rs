@@ -0,0 +1,62 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::grid::Color;
+
+// ----------------------------- 规则耗时统计 -----------------------------
+// 使用线程局部的累加器，避免在热路径上加锁
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static RULE_TIME: RefCell<HashMap<Color, Duration>> = RefCell::new(HashMap::new());
+}
+
+pub fn set_rule_profiling(enabled: bool) {
+    ENABLED.with(|flag| flag.set(enabled));
+}
+
+#[inline]
+pub fn rule_profiling_enabled() -> bool {
+    ENABLED.with(|flag| flag.get())
+}
+
+// 统计 f 的耗时并累加到对应颜色上（未开启时直接调用）
+#[inline]
+pub fn profile_rule<F: FnOnce() -> bool>(color: Color, f: F) -> bool {
+    if !rule_profiling_enabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let ok = f();
+    let elapsed = start.elapsed();
+    RULE_TIME.with(|times| *times.borrow_mut().entry(color).or_default() += elapsed);
+    ok
+}
+
+// 各颜色的累计耗时，按耗时从高到低排列
+pub fn rule_timings() -> Vec<(Color, Duration)> {
+    let mut timings: Vec<(Color, Duration)> =
+        RULE_TIME.with(|times| times.borrow().iter().map(|(c, d)| (*c, *d)).collect());
+    timings.sort_by_key(|(_, d)| std::cmp::Reverse(*d));
+    timings
+}
+
+pub fn reset_rule_timings() {
+    RULE_TIME.with(|times| times.borrow_mut().clear());
+}
+
+// 形如 "Red: 42ms (15%), Yellow: 89ms (31%), ..."
+pub fn format_rule_profile() -> String {
+    let timings = rule_timings();
+    let total: Duration = timings.iter().map(|(_, d)| *d).sum();
+    timings
+        .iter()
+        .map(|(color, d)| {
+            let percent = if total.is_zero() { 0.0 } else { d.as_secs_f64() / total.as_secs_f64() * 100.0 };
+            format!("{:?}: {}ms ({:.0}%)", color, d.as_millis(), percent)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
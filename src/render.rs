@@ -0,0 +1,836 @@
+use std::{collections::HashMap, fs, io::Cursor};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, DynamicImage, Frame, ImageBuffer, ImageOutputFormat, Rgb, RgbImage};
+use imageproc::drawing::{draw_filled_circle_mut, draw_filled_rect_mut, draw_hollow_rect_mut, draw_line_segment_mut, draw_text_mut};
+use imageproc::rect::Rect;
+use rusttype::{point, Font, Scale};
+
+use crate::config::parse_toml;
+use crate::error::BingoError;
+use crate::grid::{Color, Grid};
+use crate::i18n::{Language, Translator};
+
+// ----------------------------- 图像输出 -----------------------------
+// 按字形实际的前进宽度计算文本像素宽度（中日韩字符与拉丁字符宽度不同）
+pub fn measure_text_width(font: &Font, scale: Scale, text: &str) -> f32 {
+    font.layout(text, scale, point(0.0, 0.0))
+        .map(|glyph| glyph.position().x + glyph.unpositioned().h_metrics().advance_width)
+        .fold(0.0, f32::max)
+}
+
+// 根据字体的纵向度量计算行高，换字体后文字也不会重叠或被裁切
+pub fn compute_line_height(font: &Font, scale: Scale) -> u32 {
+    let v = font.v_metrics(scale);
+    (v.ascent - v.descent + v.line_gap).ceil() as u32
+}
+
+// ----------------------------- 颜色主题 -----------------------------
+// 图片中用到的全部颜色：每种格子的色块颜色，以及背景、规则区、文字、网格线、勾选标记
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColorTheme {
+    pub cells: HashMap<Color, [u8; 3]>,
+    pub background: [u8; 3], // 网格区域背景
+    pub panel: [u8; 3],      // 规则区背景
+    pub text: [u8; 3],
+    pub grid_line: [u8; 3],
+    pub check: [u8; 3],
+    pub highlight: [u8; 3], // 五连等高亮格子的边框
+}
+
+impl Default for ColorTheme {
+    fn default() -> Self {
+        let cells = HashMap::from([
+            (Color::Red, [255, 50, 50]),
+            (Color::Blue, [70, 130, 180]),
+            (Color::Black, [40, 40, 40]),
+            (Color::Green, [50, 205, 50]),
+            (Color::Yellow, [255, 215, 0]),
+            (Color::Purple, [128, 0, 128]),
+            (Color::White, [255, 255, 255]),
+            (Color::Orange, [255, 165, 0]),
+            (Color::Cyan, [0, 255, 255]),
+            (Color::Teal, [0, 128, 128]),
+            (Color::Pink, [255, 105, 180]),
+            (Color::Gray, [128, 128, 128]),
+            (Color::Maroon, [128, 0, 0]),
+            (Color::Navy, [0, 0, 128]),
+        ]);
+        Self {
+            cells,
+            background: [245, 245, 245], // 浅灰背景
+            panel: [255, 255, 255],      // 规则区白色背景
+            text: [80, 80, 80],          // 深灰文字
+            grid_line: [210, 210, 210],
+            check: [100, 100, 100],
+            highlight: [218, 165, 32],
+        }
+    }
+}
+
+impl ColorTheme {
+    // 深色主题：深灰背景、浅色文字，色块降低饱和度避免在暗背景上刺眼；
+    // 黑格提亮、白格压暗，两者仍能和背景区分开
+    pub fn dark_theme() -> Self {
+        let cells = HashMap::from([
+            (Color::Red, [190, 75, 75]),
+            (Color::Blue, [80, 115, 150]),
+            (Color::Black, [10, 10, 10]),
+            (Color::Green, [75, 150, 75]),
+            (Color::Yellow, [195, 170, 60]),
+            (Color::Purple, [120, 70, 130]),
+            (Color::White, [160, 160, 160]),
+            (Color::Orange, [195, 135, 55]),
+            (Color::Cyan, [70, 170, 170]),
+            (Color::Teal, [45, 110, 110]),
+            (Color::Pink, [190, 110, 150]),
+            (Color::Gray, [110, 110, 110]),
+            (Color::Maroon, [115, 40, 40]),
+            (Color::Navy, [50, 60, 125]),
+        ]);
+        Self {
+            cells,
+            background: [30, 30, 30],
+            panel: [40, 40, 40],
+            text: [210, 210, 210],
+            grid_line: [70, 70, 70],
+            check: [245, 245, 245],
+            highlight: [218, 165, 32],
+        }
+    }
+
+    // 主题文件，顶层键为 background、panel、text、grid_line、check、highlight，[cells] 表的键为颜色英文名（不区分大小写），
+    // 值均为 "#rrggbb" 形式的字符串，例如：
+    //   background = "#1e1e1e"
+    //   [cells]
+    //   Red = "#be4b4b"
+    // 没有列出的颜色沿用默认主题
+    pub fn from_toml(path: &str) -> Result<Self, BingoError> {
+        let mut theme = Self::default();
+        for (table, key, value) in parse_toml(&fs::read_to_string(path)?).map_err(BingoError::Format)? {
+            let rgb = value
+                .as_str()
+                .and_then(parse_hex_rgb)
+                .ok_or_else(|| BingoError::Format(format!("{} 应为 \"#rrggbb\" 形式的颜色", key)))?;
+            let slot = match (table.as_str(), key.as_str()) {
+                ("", "background") => &mut theme.background,
+                ("", "panel") => &mut theme.panel,
+                ("", "text") => &mut theme.text,
+                ("", "grid_line") => &mut theme.grid_line,
+                ("", "check") => &mut theme.check,
+                ("", "highlight") => &mut theme.highlight,
+                ("", _) => return Err(BingoError::Format(format!("未知的主题项: {}", key))),
+                ("cells", _) => {
+                    let color = Color::from_name(&key).ok_or_else(|| BingoError::Format(format!("未知的颜色: {}", key)))?;
+                    theme.cells.entry(color).or_default()
+                }
+                _ => return Err(BingoError::Format(format!("未知的表 [{}]，只支持 [cells]", table))),
+            };
+            *slot = rgb;
+        }
+        Ok(theme)
+    }
+
+    // cells 中没有的颜色按默认主题取色
+    pub fn cell_rgb(&self, color: Color) -> [u8; 3] {
+        self.cells.get(&color).copied().unwrap_or_else(|| Self::default().cells[&color])
+    }
+}
+
+fn parse_hex_rgb(s: &str) -> Option<[u8; 3]> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+fn svg_rgb([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// 图片布局参数
+pub struct RenderConfig {
+    pub cell_size: u32,         // 单元格尺寸
+    pub rule_font_size: f32,    // 规则文字字号
+    pub margin: u32,            // 全局边距
+    pub rule_column_width: u32, // 每一栏规则文字的宽度
+    pub rule_columns: u32,      // 规则文字分几栏排列（1 或 2）
+    pub language: Language,     // 规则文字的语言
+    pub cell_padding: u32,      // 单元格色块与格子边缘的间距，0 时色块铺满、相邻格子共用边框
+    pub corner_radius: u32,     // 色块圆角半径
+    pub font_path: Option<String>, // 运行时读取的字体文件，None 时使用内置字体
+    pub theme: ColorTheme,      // 配色
+    pub tooltips: bool,         // SVG 中每个格子带 <title>，鼠标悬停时显示该颜色的英文规则
+    pub show_color_labels: bool, // 在每个格子中央写上颜色名（语言同 language），方便色弱用户和黑白打印
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: 90,
+            rule_font_size: 13.5,
+            margin: 12,
+            rule_column_width: 310,
+            rule_columns: 1,
+            language: Language::Zh,
+            cell_padding: 4,
+            corner_radius: 8,
+            font_path: None,
+            theme: ColorTheme::default(),
+            tooltips: false,
+            show_color_labels: false,
+        }
+    }
+}
+
+impl RenderConfig {
+    // 紧凑样式：色块铺满整个格子、直角，即加入间距和圆角之前的样子
+    pub fn compact() -> Self {
+        Self { cell_padding: 0, corner_radius: 0, ..Self::default() }
+    }
+}
+
+// 色块、网格线、背景颜色，格子尺寸、间距、圆角半径
+type CellKey = ([u8; 3], [u8; 3], [u8; 3], u32, u32, u32);
+
+// 单元格背景缓存：同一题目的多张解图中，单元格背景与边框只取决于颜色和尺寸，
+// 预先渲染一次，之后每张图按行 copy_from_slice 即可
+#[derive(Default)]
+pub struct CellCache {
+    background: HashMap<CellKey, Vec<u8>>,
+}
+
+impl CellCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn cell_block(&mut self, color: Color, config: &RenderConfig) -> &[u8] {
+        let (cell_size, padding, radius) = (config.cell_size, config.cell_padding, config.corner_radius);
+        let theme = &config.theme;
+        let (fill, grid_line, background) = (theme.cell_rgb(color), theme.grid_line, theme.background);
+        self.background.entry((fill, grid_line, background, cell_size, padding, radius)).or_insert_with(|| {
+            let (fill, grid_line_color, background) = (Rgb(fill), Rgb(grid_line), Rgb(background));
+            if padding == 0 && radius == 0 {
+                let mut block = ImageBuffer::from_pixel(cell_size, cell_size, fill);
+                for d in 0..cell_size {
+                    block.put_pixel(d, 0, grid_line_color); // 上边框
+                    block.put_pixel(d, cell_size - 1, grid_line_color); // 下边框
+                    block.put_pixel(0, d, grid_line_color); // 左边框
+                    block.put_pixel(cell_size - 1, d, grid_line_color); // 右边框
+                }
+                return block.into_raw();
+            }
+            // 留出间距后画圆角色块：先用网格线颜色画外框，再向内缩 1 像素填充单元格颜色
+            let mut block = ImageBuffer::from_pixel(cell_size, cell_size, background);
+            let side = cell_size.saturating_sub(padding * 2);
+            fill_rounded_rect(&mut block, padding, padding, side, radius, grid_line_color);
+            fill_rounded_rect(&mut block, padding + 1, padding + 1, side.saturating_sub(2), radius.saturating_sub(1), fill);
+            block.into_raw()
+        })
+    }
+}
+
+// 边长为 side 的正方形，四个角用半径为 radius 的四分之一圆弧代替
+fn fill_rounded_rect(img: &mut RgbImage, x: u32, y: u32, side: u32, radius: u32, color: Rgb<u8>) {
+    if side == 0 {
+        return;
+    }
+    let r = radius.min(side / 2);
+    let (x, y, side_i, r_i) = (x as i32, y as i32, side as i32, r as i32);
+    if side > 2 * r {
+        draw_filled_rect_mut(img, Rect::at(x + r_i, y).of_size(side - 2 * r, side), color);
+        draw_filled_rect_mut(img, Rect::at(x, y + r_i).of_size(side, side - 2 * r), color);
+    }
+    if r > 0 {
+        let far = side_i - 1 - r_i;
+        for (cx, cy) in [(r_i, r_i), (far, r_i), (r_i, far), (far, far)] {
+            draw_filled_circle_mut(img, (x + cx, y + cy), r_i, color);
+        }
+    }
+}
+
+pub fn save_grid_image(
+    grid: &Grid,
+    path: &str,
+    show_checks: bool,
+    date: &str,
+    solutions_count: usize,
+    cache: &mut CellCache,
+    config: &RenderConfig,
+) -> Result<(), BingoError> {
+    let img = render_grid_image(grid, show_checks, date, solutions_count, cache, config, Overlay::default())?;
+    img.save(path)?;
+    Ok(())
+}
+
+// 解图片，并给 highlight 中的格子加上高亮边框（颜色见 ColorTheme::highlight），
+// 通常传入 grid.winning_lines() 的全部格子，突出哪几条线连成了五连；highlight 为空时与 save_grid_image 相同
+pub fn save_grid_image_with_highlight(
+    grid: &Grid,
+    path: &str,
+    date: &str,
+    solutions_count: usize,
+    cache: &mut CellCache,
+    config: &RenderConfig,
+    highlight: &[(usize, usize)],
+) -> Result<(), BingoError> {
+    let overlay = Overlay { highlight, ..Overlay::default() };
+    let img = render_grid_image(grid, true, date, solutions_count, cache, config, overlay)?;
+    img.save(path)?;
+    Ok(())
+}
+
+// 渲染为内存中的 PNG 字节，供打包输出使用
+pub fn encode_grid_png(
+    grid: &Grid,
+    show_checks: bool,
+    date: &str,
+    solutions_count: usize,
+    cache: &mut CellCache,
+    config: &RenderConfig,
+) -> Result<Vec<u8>, BingoError> {
+    let img = render_grid_image(grid, show_checks, date, solutions_count, cache, config, Overlay::default())?;
+    let mut bytes = Vec::new();
+    img.write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)?;
+    Ok(bytes)
+}
+
+pub fn encode_grid_png_with_highlight(
+    grid: &Grid,
+    date: &str,
+    solutions_count: usize,
+    cache: &mut CellCache,
+    config: &RenderConfig,
+    highlight: &[(usize, usize)],
+) -> Result<Vec<u8>, BingoError> {
+    let overlay = Overlay { highlight, ..Overlay::default() };
+    let img = render_grid_image(grid, true, date, solutions_count, cache, config, overlay)?;
+    let mut bytes = Vec::new();
+    img.write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)?;
+    Ok(bytes)
+}
+
+// 逐格勾选的动画：第一帧只勾黑格，之后每帧多勾一个格子，最后一帧停留 2 秒。
+// 勾选按行优先顺序加入，这只是近似：backtrack 与 backtrack-fc 正是按行优先顺序确定格子，
+// backtrack-ac3 的传播会提前确定后面的格子，monte-carlo 没有确定顺序，Grid 上也没有记录求解器实际的顺序
+pub fn save_solution_animation(
+    grid: &Grid,
+    path: &str,
+    date: &str,
+    solutions_count: usize,
+    frame_delay_ms: u32,
+    cache: &mut CellCache,
+    config: &RenderConfig,
+) -> Result<(), BingoError> {
+    let bytes = encode_solution_animation(grid, date, solutions_count, frame_delay_ms, cache, config)?;
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+pub fn encode_solution_animation(
+    grid: &Grid,
+    date: &str,
+    solutions_count: usize,
+    frame_delay_ms: u32,
+    cache: &mut CellCache,
+    config: &RenderConfig,
+) -> Result<Vec<u8>, BingoError> {
+    let mut partial = grid.clone();
+    for cell in partial.0.iter_mut().flatten() {
+        cell.checked = cell.color == Color::Black;
+    }
+    let order: Vec<(usize, usize)> = grid.0
+        .iter()
+        .flatten()
+        .filter(|cell| cell.checked && cell.color != Color::Black)
+        .map(|cell| (cell.x, cell.y))
+        .collect();
+
+    let mut bytes = Vec::new();
+    {
+        // 量化速度取 10：画面只有十几种纯色，最慢档的画质提升看不出来
+        let mut encoder = GifEncoder::new_with_speed(&mut bytes, 10);
+        encoder.set_repeat(Repeat::Infinite)?;
+        for step in 0..=order.len() {
+            if step > 0 {
+                let (x, y) = order[step - 1];
+                partial.0[x][y].checked = true;
+            }
+            let img = render_grid_image(&partial, true, date, solutions_count, cache, config, Overlay::default())?;
+            let delay = if step == order.len() { 2000 } else { frame_delay_ms };
+            let frame = Frame::from_parts(
+                DynamicImage::ImageRgb8(img).into_rgba8(),
+                0,
+                0,
+                Delay::from_numer_denom_ms(delay, 1),
+            );
+            encoder.encode_frame(frame)?;
+        }
+    }
+    Ok(bytes)
+}
+
+// 求解器调试：把当前的部分解渲染成 PNG，并用亮黄色边框标出正在回溯的格子
+pub fn dump_solver_state(
+    grid: &Grid,
+    depth: usize,
+    current_pos: (usize, usize),
+    path: &str,
+) -> Result<(), BingoError> {
+    let mut cache = CellCache::new();
+    let label = format!("solver depth {}", depth);
+    let overlay = Overlay { frontier: Some(current_pos), ..Overlay::default() };
+    let img = render_grid_image(grid, true, &label, 0, &mut cache, &RenderConfig::default(), overlay)?;
+    img.save(path)?;
+    Ok(())
+}
+
+// path 为 None 时使用编译时内置的 fonts/font.ttf（bundled-font 特性）
+fn load_font(path: Option<&str>) -> Result<Font<'static>, BingoError> {
+    match path {
+        Some(path) => Font::try_from_vec(fs::read(path)?).ok_or(BingoError::FontLoad),
+        None => bundled_font(),
+    }
+}
+
+#[cfg(feature = "bundled-font")]
+fn bundled_font() -> Result<Font<'static>, BingoError> {
+    Font::try_from_bytes(include_bytes!("../fonts/font.ttf")).ok_or(BingoError::FontLoad)
+}
+
+// 未内置字体时，没有配置 font_path 的渲染一律失败，不在编译期依赖 fonts/font.ttf
+#[cfg(not(feature = "bundled-font"))]
+fn bundled_font() -> Result<Font<'static>, BingoError> {
+    Err(BingoError::FontMissing)
+}
+
+// 规则区的文字（PNG 与 SVG 共用），只列出本题出现的颜色的规则
+fn rule_lines(grid: &Grid, solutions_count: usize, language: Language) -> Vec<String> {
+    let translator = Translator::new(language);
+    let mut rules = vec![" ".to_string(), " ".to_string()];
+    for color in grid.colors_present() {
+        rules.extend(translator.color_rule(color).iter().map(|line| line.to_string()));
+    }
+    rules.extend(translator.rules_footer(grid.size(), solutions_count));
+    rules
+}
+
+// 叠加在网格上的边框标记：求解器调试时正在回溯的格子，以及需要高亮的格子
+#[derive(Default, Clone, Copy)]
+struct Overlay<'a> {
+    frontier: Option<(usize, usize)>,
+    highlight: &'a [(usize, usize)],
+}
+
+fn render_grid_image(
+    grid: &Grid,
+    show_checks: bool,
+    date: &str,
+    solutions_count: usize,
+    cache: &mut CellCache,
+    config: &RenderConfig,
+    overlay: Overlay,
+) -> Result<RgbImage, BingoError> {
+    // ----------------------------- 参数配置 -----------------------------
+    let cell_size = config.cell_size;
+    let rule_font_size = config.rule_font_size;
+    let margin = config.margin;
+    let rule_column_width = config.rule_column_width;
+    let rule_columns = config.rule_columns.clamp(1, 2);
+    // 每栏保持完整宽度，分两栏时规则区整体加宽（每行中文约 20 字，半宽放不下）
+    let rule_panel_width = rule_column_width * rule_columns;
+
+    // ----------------------------- 颜色定义 -----------------------------
+    let background_color = Rgb(config.theme.background);
+    let rule_bg_color = Rgb(config.theme.panel);
+    let text_color = Rgb(config.theme.text);
+    let check_color = Rgb(config.theme.check);
+    let highlight_color = Rgb(config.theme.highlight);
+    let frontier_color = Rgb([255u8, 255u8, 0u8]);     // 调试时当前格子的边框颜色
+
+    // ----------------------------- 布局计算 -----------------------------
+    // 规则文本
+    let rule_text = rule_lines(grid, solutions_count, config.language);
+    let rules: Vec<&str> = rule_text.iter().map(String::as_str).collect();
+
+    // 加载字体
+    let font = load_font(config.font_path.as_deref())?;
+    let scale = Scale::uniform(rule_font_size);
+    let label_scale = Scale::uniform(cell_size as f32 * LABEL_SIZE_RATIO);
+    let translator = Translator::new(config.language);
+    let line_spacing = compute_line_height(&font, scale); // 行间距
+
+    // 分栏：前一半在左栏，后一半在右栏
+    let rows_per_column = rules.len().div_ceil(rule_columns as usize).max(1);
+    let columns: Vec<&[&str]> = rules.chunks(rows_per_column).collect();
+
+    // ----------------------------- 图像尺寸计算 -----------------------------
+    // 计算规则文本高度（取最高的一栏）
+    let column_height = |column: &[&str]| {
+        column
+            .iter()
+            .map(|line| (line.chars().filter(|c| *c == '\n').count() as u32 + 1) * line_spacing)
+            .sum::<u32>()
+    };
+    let text_height = margin + columns.iter().map(|column| column_height(column)).max().unwrap_or(0);
+
+    // 网格区域参数
+    let grid_side = grid.size() as u32 * cell_size;
+    let grid_area_height = grid_side + margin * 2;
+    let footer_height = 30; // 版权信息区域高度
+    
+    // 总图像尺寸
+    let img_width = rule_panel_width + grid_side + margin * 3;
+    let img_height = text_height.max(grid_area_height) + footer_height;
+
+    // ----------------------------- 绘制图像 -----------------------------
+    let mut img = ImageBuffer::from_pixel(img_width, img_height, background_color);
+
+    // 绘制规则区背景
+    for x in 0..rule_panel_width {
+        for y in 0..img_height {
+            img.put_pixel(x, y, rule_bg_color);
+        }
+    }
+
+    // 绘制规则文本
+    for (c, column) in columns.iter().enumerate() {
+        let x_pos = (c as u32 * rule_column_width + margin + 10) as i32;
+        let mut y_pos = margin as i32;
+        for line in column.iter() {
+            draw_text_mut(
+                &mut img,
+                text_color,
+                x_pos,
+                y_pos,
+                scale,
+                &font,
+                line,
+            );
+            y_pos += line_spacing as i32 * (line.matches('\n').count() as i32 + 1);
+        }
+    }
+
+    // 绘制网格区域
+    let grid_start_x = rule_panel_width + margin;
+    let grid_start_y = (img_height - grid_area_height) / 2; // 垂直居中
+    for (i, row) in grid.0.iter().enumerate() {
+        for (j, cell) in row.iter().enumerate() {
+            // 单元格坐标
+            let x = grid_start_x + j as u32 * cell_size;
+            let y = grid_start_y + i as u32 * cell_size;
+
+            // 绘制单元格背景与边框（从缓存按行拷贝）
+            let block = cache.cell_block(cell.color, config);
+            let row_bytes = (cell_size * 3) as usize;
+            let raw: &mut [u8] = &mut img;
+            for dy in 0..cell_size {
+                let start = (((y + dy) * img_width + x) * 3) as usize;
+                let src = dy as usize * row_bytes;
+                raw[start..start + row_bytes].copy_from_slice(&block[src..src + row_bytes]);
+            }
+
+            // 颜色名居中写在色块上，勾选标记画在它上面
+            if config.show_color_labels {
+                let name = translator.color_name(cell.color);
+                let v = font.v_metrics(label_scale);
+                let text_width = measure_text_width(&font, label_scale, &name);
+                let text_x = x as f32 + (cell_size as f32 - text_width) / 2.0;
+                let text_y = y as f32 + (cell_size as f32 - (v.ascent - v.descent)) / 2.0;
+                let color = Rgb(label_rgb(config.theme.cell_rgb(cell.color)));
+                draw_text_mut(&mut img, color, text_x as i32, text_y as i32, label_scale, &font, &name);
+            }
+
+            // 绘制勾选标记
+            if show_checks && cell.checked {
+                draw_line_segment_mut(
+                    &mut img,
+                    (x as f32 + 10.0, y as f32 + 10.0),
+                    (x as f32 + cell_size as f32 - 10.0, y as f32 + cell_size as f32 - 10.0),
+                    check_color,
+                );
+                draw_line_segment_mut(
+                    &mut img,
+                    (x as f32 + 10.0, y as f32 + cell_size as f32 - 10.0),
+                    (x as f32 + cell_size as f32 - 10.0, y as f32 + 10.0),
+                    check_color,
+                );
+            }
+
+            // 高亮格子沿格子边缘画边框，落在色块外的间距里，不遮挡色块和勾选标记
+            if overlay.highlight.contains(&(i, j)) {
+                for d in 0..3 {
+                    draw_hollow_rect_mut(
+                        &mut img,
+                        Rect::at((x + d) as i32, (y + d) as i32).of_size(cell_size - 2 * d, cell_size - 2 * d),
+                        highlight_color,
+                    );
+                }
+            }
+
+            // 标出求解器当前所在的格子
+            if overlay.frontier == Some((i, j)) {
+                for d in 0..3 {
+                    draw_hollow_rect_mut(
+                        &mut img,
+                        Rect::at((x + d) as i32, (y + d) as i32).of_size(cell_size - 2 * d, cell_size - 2 * d),
+                        frontier_color,
+                    );
+                }
+            }
+        }
+    }
+
+    // ----------------------------- 版权信息 -----------------------------
+    let footer = format!("Generated by BingoSolver @ {}", date);
+    let footer_scale = Scale::uniform(12.0);
+    let footer_width = measure_text_width(&font, footer_scale, &footer).ceil() as i32;
+    draw_text_mut(
+        &mut img,
+        text_color,
+        (grid_start_x + grid_side) as i32 - footer_width, // 右对齐到网格右边缘
+        (img_height - footer_height + 8) as i32, // 保持在同一高度
+        footer_scale,
+        &font,
+        &footer
+    );
+
+    Ok(img)
+}
+
+// ----------------------------- 解缩略图 -----------------------------
+const STRIP_THUMB_SIZE: u32 = 45; // 每个缩略图的边长
+const STRIP_MAX_THUMBS: usize = 50; // 超过这个数量的解不再画出，只在底部注明还剩多少个
+const STRIP_GAP: u32 = 6; // 缩略图之间以及到图片边缘的间距
+
+// 把所有解的缩略图按接近正方形的行列排进一张 PNG，便于一眼看完全部解
+// font_path 的含义与 RenderConfig::font_path 相同
+pub fn save_solutions_strip(solutions: &[Grid], path: &str, date: &str, font_path: Option<&str>) -> Result<(), BingoError> {
+    let img = render_solutions_strip(solutions, date, font_path)?;
+    img.save(path)?;
+    Ok(())
+}
+
+pub fn encode_solutions_strip(solutions: &[Grid], date: &str, font_path: Option<&str>) -> Result<Vec<u8>, BingoError> {
+    let img = render_solutions_strip(solutions, date, font_path)?;
+    let mut bytes = Vec::new();
+    img.write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)?;
+    Ok(bytes)
+}
+
+// 缩略图总是使用默认主题
+fn render_solutions_strip(solutions: &[Grid], date: &str, font_path: Option<&str>) -> Result<RgbImage, BingoError> {
+    let theme = ColorTheme::default();
+    let background_color = Rgb(theme.background);
+    let text_color = Rgb(theme.text);
+
+    let font = load_font(font_path)?;
+    let footer_scale = Scale::uniform(12.0);
+
+    let shown = &solutions[..solutions.len().min(STRIP_MAX_THUMBS)];
+    let more = (solutions.len() > shown.len()).then(|| format!("…and {} more", solutions.len() - shown.len()));
+    let footer = format!("Generated by BingoSolver @ {}", date);
+    let more_width = more.as_deref().map_or(0.0, |text| measure_text_width(&font, footer_scale, text) + 20.0);
+    let footer_width = measure_text_width(&font, footer_scale, &footer).ceil() as i32;
+
+    let cols = (shown.len() as f64).sqrt().ceil().max(1.0) as u32;
+    let rows = (shown.len() as u32).div_ceil(cols);
+    let footer_height = 30;
+    // 解很少时缩略图区域比底部文字窄，按文字宽度加宽
+    let img_width = (cols * (STRIP_THUMB_SIZE + STRIP_GAP) + STRIP_GAP).max(footer_width as u32 + more_width as u32 + STRIP_GAP * 2);
+    let img_height = rows * (STRIP_THUMB_SIZE + STRIP_GAP) + STRIP_GAP + footer_height;
+    let mut img = ImageBuffer::from_pixel(img_width, img_height, background_color);
+
+    for (k, grid) in shown.iter().enumerate() {
+        let (row, col) = (k as u32 / cols, k as u32 % cols);
+        let x = STRIP_GAP + col * (STRIP_THUMB_SIZE + STRIP_GAP);
+        let y = STRIP_GAP + row * (STRIP_THUMB_SIZE + STRIP_GAP);
+        draw_thumbnail(&mut img, grid, x, y, &theme);
+    }
+
+    let text_y = (img_height - footer_height + 8) as i32;
+    if let Some(more) = &more {
+        draw_text_mut(&mut img, text_color, STRIP_GAP as i32, text_y, footer_scale, &font, more);
+    }
+    draw_text_mut(&mut img, text_color, (img_width - STRIP_GAP) as i32 - footer_width, text_y, footer_scale, &font, &footer);
+    Ok(img)
+}
+
+// 与 render_grid_image 的网格部分相同的画法按比例缩小：色块、网格线、勾选的叉；
+// 缩略图边长除不尽格子数时，网格在缩略图内居中
+fn draw_thumbnail(img: &mut RgbImage, grid: &Grid, x0: u32, y0: u32, theme: &ColorTheme) {
+    let grid_line_color = Rgb(theme.grid_line);
+    let check_color = Rgb(theme.check);
+    let cell_size = (STRIP_THUMB_SIZE / grid.size().max(1) as u32).max(1);
+    let offset = (STRIP_THUMB_SIZE.saturating_sub(cell_size * grid.size() as u32)) / 2;
+    for (i, row) in grid.0.iter().enumerate() {
+        for (j, cell) in row.iter().enumerate() {
+            let x = (x0 + offset + j as u32 * cell_size) as i32;
+            let y = (y0 + offset + i as u32 * cell_size) as i32;
+            let rect = Rect::at(x, y).of_size(cell_size, cell_size);
+            draw_filled_rect_mut(img, rect, Rgb(theme.cell_rgb(cell.color)));
+            draw_hollow_rect_mut(img, rect, grid_line_color);
+            if cell.checked {
+                let (left, top) = (x as f32 + 1.0, y as f32 + 1.0);
+                let (right, bottom) = (x as f32 + cell_size as f32 - 2.0, y as f32 + cell_size as f32 - 2.0);
+                draw_line_segment_mut(img, (left, top), (right, bottom), check_color);
+                draw_line_segment_mut(img, (left, bottom), (right, top), check_color);
+            }
+        }
+    }
+}
+
+// ----------------------------- SVG 输出 -----------------------------
+// 与 PNG 相同的布局（单栏规则，其余按 config），纯字符串拼接，不需要字体文件；
+// 文字由浏览器用系统字体渲染，行高按字号估算
+pub fn save_grid_svg(
+    grid: &Grid,
+    path: &str,
+    show_checks: bool,
+    date: &str,
+    solutions_count: usize,
+    config: &RenderConfig,
+) -> Result<(), BingoError> {
+    fs::write(path, grid_svg(grid, show_checks, date, solutions_count, config))?;
+    Ok(())
+}
+
+pub fn grid_svg(grid: &Grid, show_checks: bool, date: &str, solutions_count: usize, config: &RenderConfig) -> String {
+    let (cell_size, margin) = (config.cell_size, config.margin);
+    let line_spacing = (config.rule_font_size * 1.6).ceil() as u32;
+    let footer_height = 30;
+
+    let lines: Vec<String> = rule_lines(grid, solutions_count, config.language)
+        .iter()
+        .flat_map(|line| line.split('\n').map(str::to_string).collect::<Vec<_>>())
+        .collect();
+    let text_height = margin + lines.len() as u32 * line_spacing;
+    let grid_side = grid.size() as u32 * cell_size;
+    let grid_area_height = grid_side + margin * 2;
+    let width = config.rule_column_width + grid_side + margin * 3;
+    let height = text_height.max(grid_area_height) + footer_height;
+    let grid_x = config.rule_column_width + margin;
+    let grid_y = (height - grid_area_height) / 2;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width,
+        h = height
+    );
+    let theme = &config.theme;
+    let text_color = svg_rgb(theme.text);
+    svg += &format!("<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n", width, height, svg_rgb(theme.background));
+    svg += &format!("<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n", config.rule_column_width, height, svg_rgb(theme.panel));
+
+    // 规则文字：每行一个 <tspan>，基线从顶部边距往下排
+    svg += &format!(
+        "<text font-family=\"sans-serif\" font-size=\"{}\" fill=\"{}\">\n",
+        config.rule_font_size, text_color
+    );
+    for (i, line) in lines.iter().enumerate() {
+        let y = margin + (i as u32 + 1) * line_spacing;
+        svg += &format!("<tspan x=\"{}\" y=\"{}\">{}</tspan>\n", margin + 10, y, escape_xml(line));
+    }
+    svg += "</text>\n";
+
+    for cell in grid.0.iter().flatten() {
+        let x = grid_x + cell.y as u32 * cell_size;
+        let y = grid_y + cell.x as u32 * cell_size;
+        let padding = config.cell_padding.min(cell_size / 2);
+        let tooltip = if config.tooltips {
+            format!("><title>{}</title></rect", escape_xml(&cell_tooltip(cell.color)))
+        } else {
+            "/".to_string()
+        };
+        svg += &format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" rx=\"{}\" fill=\"{}\" stroke=\"{}\"{}>\n",
+            x + padding, y + padding, config.corner_radius, svg_rgb(theme.cell_rgb(cell.color)), svg_rgb(theme.grid_line), tooltip, s = cell_size - padding * 2
+        );
+        if config.show_color_labels {
+            let fill = theme.cell_rgb(cell.color);
+            svg += &format!(
+                "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{:.1}\" fill=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                x + cell_size / 2,
+                y + cell_size / 2,
+                cell_size as f32 * LABEL_SIZE_RATIO,
+                svg_rgb(label_rgb(fill)),
+                escape_xml(&Translator::new(config.language).color_name(cell.color))
+            );
+        }
+        if show_checks && cell.checked {
+            let (x0, y0, x1, y1) = (x + 10, y + 10, x + cell_size - 10, y + cell_size - 10);
+            svg += &format!(
+                "<path d=\"M{x0} {y0} L{x1} {y1} M{x0} {y1} L{x1} {y0}\" stroke=\"{c}\" stroke-width=\"2\"/>\n",
+                x0 = x0, y0 = y0, x1 = x1, y1 = y1, c = svg_rgb(theme.check)
+            );
+        }
+    }
+
+    // 版权信息，右对齐到网格右边缘
+    svg += &format!(
+        "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"12\" fill=\"{}\" text-anchor=\"end\">{}</text>\n",
+        grid_x + grid_side,
+        height - footer_height + 20,
+        text_color,
+        escape_xml(&format!("Generated by BingoSolver @ {}", date))
+    );
+    svg += "</svg>\n";
+    svg
+}
+
+// 悬停提示：颜色英文名加上英文规则，折行合并成一句，如 "Red: A red cell needs at least one checked cell around it."
+fn cell_tooltip(color: Color) -> String {
+    let rule = Translator::new(Language::En).color_rule(color).join(" ");
+    if rule.is_empty() {
+        format!("{:?}: no rule.", color)
+    } else {
+        format!("{:?}: {}", color, rule)
+    }
+}
+
+// 颜色名的字号相对格子尺寸的比例，90 像素的格子约 18 号字，最长的英文名（Maroon、Purple）也放得下
+const LABEL_SIZE_RATIO: f32 = 0.2;
+
+// 按色块亮度（ITU-R BT.601 加权）选文字颜色：浅色块上用深色字，深色块上用白字
+fn label_rgb([r, g, b]: [u8; 3]) -> [u8; 3] {
+    let luminance = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    if luminance > 140.0 { [40, 40, 40] } else { [255, 255, 255] }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 把文字画在黑底上，返回有笔画的像素所占的横向宽度
+    fn inked_width(font: &Font, scale: Scale, text: &str) -> u32 {
+        let mut img = RgbImage::new(800, 100);
+        draw_text_mut(&mut img, Rgb([255, 255, 255]), 10, 10, scale, font, text);
+        let columns: Vec<u32> = (0..img.width())
+            .filter(|&x| (0..img.height()).any(|y| img.get_pixel(x, y)[0] > 0))
+            .collect();
+        match (columns.first(), columns.last()) {
+            (Some(first), Some(last)) => last - first + 1,
+            _ => 0,
+        }
+    }
+
+    #[cfg(feature = "bundled-font")]
+    #[test]
+    fn measured_width_matches_rendered_cjk_text() {
+        let font = load_font(None).unwrap();
+        let scale = Scale::uniform(24.0);
+        let text = "每日宾果规则说明";
+        let measured = measure_text_width(&font, scale, text);
+        let rendered = inked_width(&font, scale, text) as f32;
+        assert!(rendered > 0.0);
+        // 首尾字形的左右留白不会被画出来，允许 10% 的误差
+        assert!((rendered - measured).abs() <= measured * 0.1, "实际绘制宽度 {} 与计算宽度 {} 相差超过 10%", rendered, measured);
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::NaiveDate;
+use log::{info, warn};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::error::BingoError;
+use crate::storage::solutions_json_file;
+
+// ----------------------------- HTTP 服务 -----------------------------
+// 只读地提供 data/ 目录下的题目，供网页前端直接请求，不需要访问文件系统：
+//   GET /puzzle/today          当天的 solutions.json
+//   GET /puzzle/<日期>          指定日期（YYYY-MM-DD）的 solutions.json
+//   GET /puzzle/today/image    当天的 blank.png（/puzzle/<日期>/image 同理）
+// 请求量很小，不引入异步运行时，用标准库按顺序逐个处理连接；每个响应之后关闭连接
+pub struct PuzzleServer {
+    root: PathBuf,
+    today: fn() -> String,
+}
+
+// 解析请求行之前等待客户端的最长时间，避免一个不发数据的连接卡住整个服务
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    gzip: bool, // 正文为 gzip 压缩的数据（solutions.json.gz 原样发送）
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn error(status: &'static str) -> Self {
+        Self { status, content_type: "text/plain; charset=utf-8", gzip: false, body: status.as_bytes().to_vec() }
+    }
+}
+
+impl PuzzleServer {
+    // today 返回当天的日期（YYYY-MM-DD），由调用方决定按哪个时区计算
+    pub fn new(root: impl AsRef<Path>, today: fn() -> String) -> Self {
+        Self { root: root.as_ref().to_path_buf(), today }
+    }
+
+    // 绑定 addr 后一直运行；单个连接的读写错误只记录日志，不会让服务退出
+    pub fn run(&self, addr: &str) -> Result<(), BingoError> {
+        let listener = TcpListener::bind(addr)?;
+        info!("HTTP 服务已启动: http://{}/puzzle/today，数据目录 {}", addr, self.root.display());
+        for stream in listener.incoming() {
+            let result = stream.and_then(|stream| self.handle(stream));
+            if let Err(e) = result {
+                warn!("⚠️ 处理 HTTP 请求失败: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    fn handle(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut reader = BufReader::new(&stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // 丢弃其余请求头，直到空行
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 0 && header != "\r\n" && header != "\n" {
+            header.clear();
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let response = match (parts.next(), parts.next()) {
+            (Some("GET"), Some(target)) => self.route(target),
+            (Some(_), Some(_)) => Response::error("405 Method Not Allowed"),
+            _ => Response::error("400 Bad Request"),
+        };
+        info!("{} -> {}", request_line.trim_end(), response.status);
+
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n",
+            response.status,
+            response.content_type,
+            response.body.len(),
+        )?;
+        if response.gzip {
+            write!(stream, "Content-Encoding: gzip\r\n")?;
+        }
+        write!(stream, "\r\n")?;
+        stream.write_all(&response.body)?;
+        stream.flush()
+    }
+
+    fn route(&self, target: &str) -> Response {
+        // 忽略查询参数，例如前端为了绕过缓存加的 ?t=...
+        let path = target.split('?').next().unwrap_or_default();
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let (date, image) = match segments.as_slice() {
+            ["puzzle", date] => (*date, false),
+            ["puzzle", date, "image"] => (*date, true),
+            _ => return Response::error("404 Not Found"),
+        };
+        let date = if date == "today" { (self.today)() } else { date.to_string() };
+        // 只接受合法日期，日期同时是目录名，这样也挡住了 ../ 之类的路径
+        if NaiveDate::parse_from_str(&date, "%Y-%m-%d").is_err() {
+            return Response::error("400 Bad Request");
+        }
+
+        let folder = self.root.join(&date);
+        if image {
+            return match fs::read(folder.join("blank.png")) {
+                Ok(body) => Response { status: "200 OK", content_type: "image/png", gzip: false, body },
+                Err(_) => Response::error("404 Not Found"),
+            };
+        }
+        // 与 load_solutions_json 的查找顺序一致；.gz 原样发送，由客户端解压
+        let json = "application/json; charset=utf-8";
+        match solutions_json_file(&folder.join("solutions.json")).and_then(|(path, gzip)| Some((fs::read(path).ok()?, gzip))) {
+            Some((body, gzip)) => Response { status: "200 OK", content_type: json, gzip, body },
+            None => Response::error("404 Not Found"),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::config::MAX_GRID_SIZE;
+use crate::error::DecodeError;
+use crate::grid::Color;
+
+// ----------------------------- 分享参数 -----------------------------
+// 把颜色布局编码成可以直接放进 URL 的短字符串：
+// 第一个字节为边长，其后每个格子占半个字节（颜色在 Color::ALL 中的下标，高半字节在前），按行优先排列，
+// 格子数为奇数时最后半个字节补 0；整体再做 URL 安全的 base64（- 和 _，不带 = 填充）。5 × 5 时为 19 个字符
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub fn grid_to_url_param(color_grid: &[Vec<Color>]) -> String {
+    let indexes: Vec<u8> = color_grid
+        .iter()
+        .flatten()
+        .map(|color| Color::ALL.iter().position(|c| c == color).unwrap() as u8)
+        .collect();
+    let mut bytes = vec![color_grid.len() as u8];
+    bytes.extend(indexes.chunks(2).map(|pair| pair[0] << 4 | pair.get(1).copied().unwrap_or(0)));
+    encode_base64(&bytes)
+}
+
+pub fn grid_from_url_param(s: &str) -> Result<Vec<Vec<Color>>, DecodeError> {
+    let bytes = decode_base64(s.trim())?;
+    let Some((&size, packed)) = bytes.split_first() else {
+        return Err(DecodeError::Empty);
+    };
+    let n = size as usize;
+    if !(1..=MAX_GRID_SIZE).contains(&n) {
+        return Err(DecodeError::InvalidSize(n));
+    }
+    let expected = (n * n).div_ceil(2);
+    if packed.len() != expected {
+        return Err(DecodeError::WrongLength { expected, found: packed.len() });
+    }
+
+    let indexes = packed.iter().flat_map(|byte| [byte >> 4, byte & 0x0F]);
+    let colors = indexes
+        .take(n * n)
+        .enumerate()
+        .map(|(cell, index)| {
+            Color::ALL.get(index as usize).copied().ok_or(DecodeError::UnknownColorIndex { cell, index })
+        })
+        .collect::<Result<Vec<Color>, DecodeError>>()?;
+    Ok(colors.chunks(n).map(<[Color]>::to_vec).collect())
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &b)| group | (b as u32) << (16 - 8 * i));
+        // 1 个字节对应 2 个字符，2 个对应 3 个，3 个对应 4 个
+        for k in 0..=chunk.len() {
+            out.push(ALPHABET[(group >> (18 - 6 * k) & 0x3F) as usize] as char);
+        }
+    }
+    out
+}
+
+fn decode_base64(s: &str) -> Result<Vec<u8>, DecodeError> {
+    let values = s
+        .chars()
+        .enumerate()
+        .map(|(position, c)| {
+            ALPHABET
+                .iter()
+                .position(|&a| a as char == c)
+                .map(|v| v as u32)
+                .ok_or(DecodeError::InvalidCharacter { position, found: c })
+        })
+        .collect::<Result<Vec<u32>, DecodeError>>()?;
+    // 每 4 个字符还原 3 个字节；末尾只剩 1 个字符时不足一个字节，说明字符串被截断
+    if values.len() % 4 == 1 {
+        return Err(DecodeError::Truncated);
+    }
+    let mut bytes = Vec::new();
+    for chunk in values.chunks(4) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &v)| group | v << (18 - 6 * i));
+        for k in 0..chunk.len() - 1 {
+            bytes.push((group >> (16 - 8 * k)) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_param_round_trips() {
+        // 5 × 5 时 1 + 13 个字节，编码为 19 个字符
+        let layout: Vec<Vec<Color>> = (0..5).map(|x| (0..5).map(|y| Color::ALL[(x * 5 + y) % Color::ALL.len()]).collect()).collect();
+        let param = grid_to_url_param(&layout);
+        assert_eq!(param.len(), 19);
+        assert!(param.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(grid_from_url_param(&param), Ok(layout));
+
+        for n in 1..=MAX_GRID_SIZE {
+            let layout = vec![vec![Color::Navy; n]; n];
+            assert_eq!(grid_from_url_param(&grid_to_url_param(&layout)), Ok(layout));
+        }
+        // 两侧的空白会被忽略
+        let layout = vec![vec![Color::Red, Color::White], vec![Color::Black, Color::Teal]];
+        assert_eq!(grid_from_url_param(&format!(" {}\n", grid_to_url_param(&layout))), Ok(layout));
+    }
+
+    #[test]
+    fn url_param_reports_decode_errors() {
+        assert_eq!(grid_from_url_param(""), Err(DecodeError::Empty));
+        assert_eq!(grid_from_url_param("BQ*A"), Err(DecodeError::InvalidCharacter { position: 2, found: '*' }));
+        assert_eq!(grid_from_url_param("BQAAA"), Err(DecodeError::Truncated));
+        assert_eq!(grid_from_url_param(&encode_base64(&[0])), Err(DecodeError::InvalidSize(0)));
+        assert_eq!(grid_from_url_param(&encode_base64(&[9, 0, 0])), Err(DecodeError::InvalidSize(9)));
+        assert_eq!(grid_from_url_param(&encode_base64(&[2, 0])), Err(DecodeError::WrongLength { expected: 2, found: 1 }));
+        assert_eq!(grid_from_url_param(&encode_base64(&[2, 0x01, 0xE0])), Err(DecodeError::UnknownColorIndex { cell: 2, index: 14 }));
+        // 边长为奇数时末尾补的半个字节不参与解码
+        assert_eq!(grid_from_url_param(&encode_base64(&[1, 0x1F])), Ok(vec![vec![Color::Blue]]));
+    }
+}
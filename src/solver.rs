@@ -0,0 +1,1173 @@
+use log::{debug, error, info, warn};
+use rand::Rng;
+use rayon::prelude::*;
+use std::{
+    cell::{Cell as CounterCell, RefCell},
+    collections::HashSet,
+    path::PathBuf,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use crate::bitgrid::{board_mask, line_masks, BitGrid, NEIGHBOR_MASKS};
+use crate::config::{GridConfig, MAX_CELLS, MAX_GRID_SIZE};
+use crate::error::BingoError;
+use crate::grid::{grids_are_symmetric, Cell, Color, Grid};
+use crate::profiling::profile_rule;
+use crate::render::dump_solver_state;
+
+// ----------------------------- 求解器实现 -----------------------------
+pub struct MonteCarloEstimate {
+    pub samples: usize,
+    pub hits: usize,
+    pub estimated_solutions: f64,
+    pub found: Vec<Grid>, // 采样命中的不重复解
+}
+
+// 最近一次搜索的统计，用于调整 max_checked、找出求解器最吃力的颜色布局。
+// branches_pruned 为未走到叶子就被剪掉的子树数：前向检查或增量校验不通过、勾选数超过上限、弧相容传播发现矛盾
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SolverStats {
+    pub nodes_visited: u64,
+    pub branches_pruned: u64,
+    pub time_elapsed: Duration,
+}
+
+// 求解策略
+// Backtrack：朴素回溯，只在叶子节点校验
+// BacktrackFc：回溯 + 红格前向检查（默认，即原有算法）
+// BacktrackAc3：回溯，每次赋值后对所有规则做弧相容传播，删去不可能的取值
+// MonteCarlo：随机采样，只能找到部分解，适合估计大网格的解数量
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolverStrategy {
+    Backtrack,
+    BacktrackFc,
+    BacktrackAc3,
+    MonteCarlo,
+}
+
+impl SolverStrategy {
+    pub const ALL: [SolverStrategy; 4] = [
+        SolverStrategy::Backtrack,
+        SolverStrategy::BacktrackFc,
+        SolverStrategy::BacktrackAc3,
+        SolverStrategy::MonteCarlo,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SolverStrategy::Backtrack => "backtrack",
+            SolverStrategy::BacktrackFc => "backtrack-fc",
+            SolverStrategy::BacktrackAc3 => "backtrack-ac3",
+            SolverStrategy::MonteCarlo => "monte-carlo",
+        }
+    }
+}
+
+impl FromStr for SolverStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SolverStrategy::ALL
+            .into_iter()
+            .find(|strategy| strategy.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = SolverStrategy::ALL.iter().map(|s| s.name()).collect();
+                format!("未知的求解策略 {}，可选: {}", s, names.join(", "))
+            })
+    }
+}
+
+pub struct SolverBuilder {
+    color_grid: Vec<Vec<Color>>,
+    config: Option<GridConfig>, // 未指定时按 color_grid 确定
+    max_checked: Option<usize>,
+    strategy: SolverStrategy,
+    monte_carlo_samples: usize,
+    debug_dump: Option<(PathBuf, u64)>,
+}
+
+impl SolverBuilder {
+    // 默认为 auto_max_checked 按颜色布局估算的上限
+    pub fn max_checked(mut self, max_checked: usize) -> Self {
+        self.max_checked = Some(max_checked);
+        self
+    }
+
+    // 默认按 color_grid 的行数确定；两者不一致时 build 返回错误
+    pub fn grid_config(mut self, config: GridConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    pub fn strategy(mut self, strategy: SolverStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    // MonteCarlo 策略的采样次数
+    pub fn monte_carlo_samples(mut self, samples: usize) -> Self {
+        self.monte_carlo_samples = samples;
+        self
+    }
+
+    pub fn debug_dump(mut self, dir: impl Into<PathBuf>, every: u64) -> Self {
+        self.debug_dump = Some((dir.into(), every.max(1)));
+        self
+    }
+
+    // 颜色网格为空、不是方形、边长超过 MAX_GRID_SIZE 或与 grid_config 不一致时返回 InvalidInput
+    pub fn build(self) -> Result<Solver, BingoError> {
+        let config = GridConfig::of(&self.color_grid)?;
+        if let Some(expected) = self.config {
+            if expected != config {
+                return Err(BingoError::InvalidInput(format!(
+                    "颜色网格为 {0} × {0}，与网格尺寸 {1} × {1} 不一致",
+                    config.size, expected.size
+                )));
+            }
+        }
+        let n = config.size;
+        let blank = Grid::new_blank(&self.color_grid);
+        let color_groups = blank.ordered_color_groups();
+
+        // 弧相容传播用：每条规则涉及的格子，以及每个格子参与的规则
+        let mut constraints = Vec::new();
+        let mut constraints_of: Vec<Vec<usize>> = vec![Vec::new(); config.cells()];
+        for (_, cells) in &color_groups {
+            for &(x, y) in cells {
+                let scope: Vec<usize> = blank.rule_dependencies(x, y).iter().map(|(i, j)| i * n + j).collect();
+                for &var in &scope {
+                    constraints_of[var].push(constraints.len());
+                }
+                constraints.push(((x, y), scope));
+            }
+        }
+
+        // 增量校验用：规则依赖的非黑格中按行优先顺序最靠后的一个确定之后，这条规则的结果就不会再变。
+        // ready_rules[k] 为前 k 个格子确定后即可判定的规则格子（黑格初始化时已勾选，视为一开始就确定）
+        let mut ready_rules = vec![Vec::new(); n * n + 1];
+        for (color, cells) in &color_groups {
+            for &(x, y) in cells {
+                let deps = BitGrid::rule_mask(*color, x, y, n) & board_mask(n);
+                let ready_at = (0..n * n)
+                    .filter(|&p| deps & (1 << ((p / n) * MAX_GRID_SIZE + p % n)) != 0)
+                    .filter(|&p| self.color_grid[p / n][p % n] != Color::Black)
+                    .map(|p| p + 1)
+                    .max()
+                    .unwrap_or(0);
+                ready_rules[ready_at].push((*color, x, y));
+            }
+        }
+
+        let max_checked = self.max_checked.unwrap_or_else(|| auto_max_checked(&self.color_grid));
+        Ok(Solver {
+            color_grid: self.color_grid,
+            config,
+            max_checked,
+            strategy: self.strategy,
+            monte_carlo_samples: self.monte_carlo_samples,
+            red_cells: color_groups
+                .iter()
+                .filter(|(color, _)| *color == Color::Red)
+                .flat_map(|(_, cells)| cells.iter().copied())
+                .collect(),
+            color_groups,
+            constraints,
+            constraints_of,
+            ready_rules,
+            line_masks: line_masks(n),
+            board_mask: board_mask(n),
+            debug_dump: self.debug_dump,
+            nodes_visited: CounterCell::new(0),
+            branches_pruned: CounterCell::new(0),
+            search_started: CounterCell::new(None),
+            time_elapsed: CounterCell::new(Duration::ZERO),
+            progress_interval: 1_000_000,
+            seen: RefCell::new(HashSet::new()),
+            deadline: CounterCell::new(None),
+            timed_out: CounterCell::new(false),
+            pre_fixed: Vec::new(),
+            fixed_at: vec![None; n * n],
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct Solver {
+    color_grid: Vec<Vec<Color>>,
+    config: GridConfig,
+    max_checked: usize,
+    strategy: SolverStrategy,
+    monte_carlo_samples: usize,
+    color_groups: Vec<(Color, Vec<(usize, usize)>)>, // 按颜色预先分组的格子，叶子节点校验时复用
+    red_cells: Vec<(usize, usize)>, // 前向检查只需要红格
+    constraints: Vec<((usize, usize), Vec<usize>)>, // 规则格子及其依赖格子的下标（x * size + y）
+    constraints_of: Vec<Vec<usize>>, // 每个格子参与的规则在 constraints 中的下标
+    ready_rules: Vec<Vec<(Color, usize, usize)>>, // 按行优先顺序确定前 k 个格子后即可判定的规则格子
+    line_masks: Vec<u64>, // 位棋盘上所有可能的五连
+    board_mask: u64, // 位棋盘上属于网格内的格子
+    debug_dump: Option<(PathBuf, u64)>, // 调试输出目录与间隔节点数
+    nodes_visited: CounterCell<u64>,
+    branches_pruned: CounterCell<u64>,
+    search_started: CounterCell<Option<Instant>>, // 最近一次搜索的开始时间，结束时算出 time_elapsed
+    time_elapsed: CounterCell<Duration>,
+    progress_interval: u64, // solve_with_progress 回调的间隔节点数
+    seen: RefCell<HashSet<u64>>, // 已找到解的 grid_checked_hash
+    deadline: CounterCell<Option<Instant>>, // solve_with_timeout 的截止时间
+    timed_out: CounterCell<bool>, // 本次搜索是否因超时提前结束
+    pre_fixed: Vec<(usize, usize, bool)>, // 调用方强制指定勾选状态的格子
+    fixed_at: Vec<Option<bool>>, // 按下标（x * size + y）查 pre_fixed，回溯时每个节点都要查
+}
+
+// 每访问这么多个节点检查一次是否超时，避免每个节点都读时钟
+const DEADLINE_CHECK_INTERVAL: u64 = 1000;
+
+// 弧相容传播中每个格子的取值域
+const CAN_UNCHECK: u8 = 0b01;
+const CAN_CHECK: u8 = 0b10;
+
+fn domain_bit(checked: bool) -> u8 {
+    if checked { CAN_CHECK } else { CAN_UNCHECK }
+}
+
+impl Solver {
+    pub fn new(color_grid: Vec<Vec<Color>>, config: GridConfig) -> Result<Self, BingoError> {
+        Self::builder(color_grid).grid_config(config).build()
+    }
+
+    pub fn builder(color_grid: Vec<Vec<Color>>) -> SolverBuilder {
+        SolverBuilder {
+            config: None,
+            color_grid,
+            max_checked: None,
+            strategy: SolverStrategy::BacktrackFc,
+            monte_carlo_samples: 1_000_000,
+            debug_dump: None,
+        }
+    }
+
+    pub fn strategy(&self) -> SolverStrategy {
+        self.strategy
+    }
+
+    // 最近一次 solve 访问的节点数（solve_parallel 为各任务之和）
+    pub fn nodes_visited(&self) -> u64 {
+        self.nodes_visited.get()
+    }
+
+    // 最近一次搜索（solve 系列、count_solutions 或 solve_streaming）的统计；
+    // solve_streaming 的耗时与节点数随迭代累计，截至上一次产出解或迭代结束
+    pub fn stats(&self) -> SolverStats {
+        SolverStats {
+            nodes_visited: self.nodes_visited.get(),
+            branches_pruned: self.branches_pruned.get(),
+            time_elapsed: self.time_elapsed.get(),
+        }
+    }
+
+    // solve_with_progress 每访问 every 个节点调用一次回调，默认 1_000_000
+    pub fn set_progress_interval(&mut self, every: u64) {
+        self.progress_interval = every.max(1);
+    }
+
+    // 每访问 every 个节点就把当前状态渲染到 dir 目录下
+    pub fn with_debug_dump(mut self, dir: impl Into<PathBuf>, every: u64) -> Self {
+        self.debug_dump = Some((dir.into(), every.max(1)));
+        self
+    }
+
+    // 强制指定一些格子的勾选状态，求解时这些格子不再分支，只找与之一致的解（如给定若干提示格）。
+    // 同一格子出现多次时以最后一次为准；把黑格指定为不勾选时题目无解
+    pub fn with_fixed_cells(mut self, fixed: Vec<(usize, usize, bool)>) -> Self {
+        let n = self.config.size;
+        self.fixed_at = vec![None; n * n];
+        for &(x, y, checked) in &fixed {
+            assert!(x < n && y < n, "固定格子({},{})超出 {} × {} 网格", x, y, n, n);
+            self.fixed_at[x * n + y] = Some(checked);
+        }
+        self.pre_fixed = fixed;
+        self
+    }
+
+    pub fn fixed_cells(&self) -> &[(usize, usize, bool)] {
+        &self.pre_fixed
+    }
+
+    // 求解时不分支的格子：黑格与固定格子
+    #[inline]
+    fn is_decided(&self, x: usize, y: usize) -> bool {
+        self.color_grid[x][y] == Color::Black || self.fixed_at[x * self.config.size + y].is_some()
+    }
+
+    // 有黑格被固定为不勾选时无解
+    fn fixed_cells_consistent(&self) -> bool {
+        let ok = !self.pre_fixed.iter().any(|&(x, y, checked)| !checked && self.color_grid[x][y] == Color::Black);
+        if !ok {
+            info!("固定为不勾选的格子中有黑格，题目无解");
+        }
+        ok
+    }
+
+    // grid 只在需要导出时才构造，位棋盘回溯时避免每个节点都转换
+    fn maybe_dump(&self, grid: impl FnOnce() -> Grid, current_pos: (usize, usize)) {
+        let Some((dir, every)) = &self.debug_dump else {
+            return;
+        };
+        let nodes = self.nodes_visited.get();
+        if !nodes.is_multiple_of(*every) {
+            return;
+        }
+        let depth = current_pos.0 * self.config.size + current_pos.1;
+        let path = dir.join(format!("node_{:010}_depth_{:02}.png", nodes, depth));
+        if let Err(e) = dump_solver_state(&grid(), depth, current_pos, &path.to_string_lossy()) {
+            warn!("求解器状态导出失败 {}: {}", path.display(), e);
+        }
+    }
+
+    fn initialize_grid(&self) -> Grid {
+        let n = self.config.size;
+        Grid(
+            (0..n).map(|i| {
+                (0..n).map(|j| Cell {
+                    x: i,
+                    y: j,
+                    color: self.color_grid[i][j],
+                    // 黑格默认勾选，固定格子按指定状态
+                    checked: self.color_grid[i][j] == Color::Black || self.fixed_at[i * n + j] == Some(true),
+                }).collect()
+            }).collect()
+        )
+    }
+
+    fn next_position(&self, x: usize, y: usize) -> (usize, usize) {
+        if y + 1 == self.config.size { (x + 1, 0) } else { (x, y + 1) }
+    }
+
+    pub fn solve(&self) -> Vec<Grid> {
+        self.solve_inner(None)
+    }
+
+    // 与 solve 相同，同时返回本次求解的统计
+    pub fn solve_with_stats(&self) -> (Vec<Grid>, SolverStats) {
+        let solutions = self.solve();
+        (solutions, self.stats())
+    }
+
+    // 每次搜索开始时清零统计与超时标记
+    fn start_search(&self) {
+        self.nodes_visited.set(0);
+        self.branches_pruned.set(0);
+        self.timed_out.set(false);
+        self.time_elapsed.set(Duration::ZERO);
+        self.search_started.set(Some(Instant::now()));
+    }
+
+    fn finish_search(&self) {
+        if let Some(started) = self.search_started.get() {
+            self.time_elapsed.set(started.elapsed());
+        }
+    }
+
+    #[inline]
+    fn prune(&self) {
+        self.branches_pruned.set(self.branches_pruned.get() + 1);
+    }
+
+    // 与 solve 相同，回溯过程中每访问 progress_interval 个节点，以累计访问的节点数调用一次 callback。
+    // 难题可能要跑很久，调用方可以借此打印进度；monte-carlo 策略没有节点的概念，不会回调
+    pub fn solve_with_progress<F: Fn(u64)>(&self, callback: F) -> Vec<Grid> {
+        self.solve_inner(Some(&callback))
+    }
+
+    fn solve_inner(&self, progress: Option<&dyn Fn(u64)>) -> Vec<Grid> {
+        self.start_search();
+        if !is_satisfiable_quick(&self.color_grid, self.max_checked) {
+            info!("题目未通过快速可满足性检查，跳过求解");
+            return Vec::new();
+        }
+        if !self.fixed_cells_consistent() {
+            return Vec::new();
+        }
+
+        let mut solutions = Vec::new();
+        self.seen.borrow_mut().clear();
+        let mut current_grid = self.initialize_grid();
+        let initial_checked = current_grid.checked_count();
+        match self.strategy {
+            SolverStrategy::Backtrack | SolverStrategy::BacktrackFc => {
+                let bits = BitGrid::from_grid(&current_grid);
+                let found = RefCell::new(Vec::new());
+                let output = SearchOutput { sink: Sink::Collect(&found), progress };
+                run_trampoline(self.backtrack(0, 0, bits, output, initial_checked, Step::Done));
+                solutions = found.into_inner();
+            }
+            SolverStrategy::BacktrackAc3 => {
+                let mut domains = self.initial_domains();
+                let all_constraints: Vec<usize> = (0..self.constraints.len()).collect();
+                if self.propagate(&mut current_grid, &mut domains, all_constraints) {
+                    self.backtrack_ac3(0, &mut current_grid, &mut domains, &mut solutions, initial_checked, progress);
+                }
+            }
+            SolverStrategy::MonteCarlo => {
+                solutions = self.sample_monte_carlo(self.monte_carlo_samples, &mut rand::thread_rng()).found;
+            }
+        }
+        self.finish_search();
+        self.validate_solutions(solutions)
+    }
+
+    // 与 solve 相同，但最多搜索 duration：超时后立即结束搜索，返回已经找到的解，第二项为 true。
+    // 时间限制只约束搜索本身，之后对已找到的解的复核不计在内，解很多时会多花一些时间。
+    // 只对回溯类策略生效（monte-carlo 按采样次数结束）；solve_parallel 不受限制
+    pub fn solve_with_timeout(&self, duration: Duration) -> (Vec<Grid>, bool) {
+        self.deadline.set(Some(Instant::now() + duration));
+        let solutions = self.solve_inner(None);
+        self.deadline.set(None);
+        (solutions, self.timed_out.get())
+    }
+
+    // 与 solve 相同，但互为旋转或翻转的解只保留最先找到的一个
+    pub fn solve_canonical(&self) -> Vec<Grid> {
+        canonical_solutions(self.solve())
+    }
+
+    // 只统计解的数量：与 solve 走同一套回溯，叶子节点只加计数，不克隆 Grid，解很多时省内存。
+    // 只对 Backtrack、BacktrackFc 生效，其余策略退回 solve().len()
+    pub fn count_solutions(&self) -> usize {
+        if !matches!(self.strategy, SolverStrategy::Backtrack | SolverStrategy::BacktrackFc) {
+            return self.solve().len();
+        }
+        self.start_search();
+        if !is_satisfiable_quick(&self.color_grid, self.max_checked) || !self.fixed_cells_consistent() {
+            return 0;
+        }
+        let initial = self.initialize_grid();
+        let count = CounterCell::new(0);
+        let output = SearchOutput { sink: Sink::Count(&count), progress: None };
+        run_trampoline(self.backtrack(0, 0, BitGrid::from_grid(&initial), output, initial.checked_count(), Step::Done));
+        self.finish_search();
+        count.get()
+    }
+
+    // 按需逐个产出解的迭代器，顺序与 solve 相同；调用方 take(N) 时只搜索到第 N 个解为止。
+    // 除 Backtrack 外的策略都按 BacktrackFc 的剪枝搜索（解的集合相同），nodes_visited 随迭代累计
+    pub fn solve_streaming(&self) -> SolverIter<'_> {
+        self.start_search();
+        let initial = self.initialize_grid();
+        let stack = if !self.fixed_cells_consistent() {
+            Vec::new()
+        } else if is_satisfiable_quick(&self.color_grid, self.max_checked) {
+            vec![(0, BitGrid::from_grid(&initial), initial.checked_count())]
+        } else {
+            info!("题目未通过快速可满足性检查，跳过求解");
+            Vec::new()
+        };
+        SolverIter { solver: self, stack }
+    }
+
+    // 恰好有一个解：找到第二个解即停止搜索
+    pub fn has_unique_solution(&self) -> bool {
+        self.solve_streaming().take(2).count() == 1
+    }
+
+    // 至少有 n 个解：找到第 n 个解即停止搜索，n 为 0 时不搜索。
+    // 用来在完整求解之前淘汰解太少或太多（solution_count_at_least(max + 1)）的题目
+    pub fn solution_count_at_least(&self, n: usize) -> bool {
+        n == 0 || self.solve_streaming().nth(n - 1).is_some()
+    }
+
+    // 并行求解：按行优先顺序取前几个非黑格，枚举它们的勾选组合作为前缀，每个前缀的子树作为一个 rayon 任务。
+    // 求解器用 Cell/RefCell 记录节点数和去重状态，不能跨线程共享，因此每个任务持有一份克隆。
+    // 前缀按先勾选、后不勾选的顺序排列，结果按前缀顺序拼接，与 solve 的输出顺序完全一致。
+    // 只对 Backtrack、BacktrackFc 生效，其余策略直接调用 solve
+    pub fn solve_parallel(&self) -> Vec<Grid> {
+        if !matches!(self.strategy, SolverStrategy::Backtrack | SolverStrategy::BacktrackFc) {
+            return self.solve();
+        }
+        self.start_search();
+        if !is_satisfiable_quick(&self.color_grid, self.max_checked) {
+            info!("题目未通过快速可满足性检查，跳过求解");
+            return Vec::new();
+        }
+        if !self.fixed_cells_consistent() {
+            return Vec::new();
+        }
+
+        let n = self.config.size;
+        let initial = self.initialize_grid();
+        let initial_checked = initial.checked_count();
+        let base = BitGrid::from_grid(&initial);
+
+        // 任务数取线程数的 4 倍左右，子树大小不均时便于负载均衡
+        let split_cells = (rayon::current_num_threads() * 4).next_power_of_two().trailing_zeros() as usize;
+        let split: Vec<(usize, usize)> = (0..n * n)
+            .map(|i| (i / n, i % n))
+            .filter(|&(x, y)| !self.is_decided(x, y))
+            .take(split_cells)
+            .collect();
+        let Some(&(last_x, last_y)) = split.last() else {
+            return self.solve();
+        };
+        let (start_x, start_y) = self.next_position(last_x, last_y);
+
+        let tasks: Vec<(BitGrid, usize, Solver)> = (0..1usize << split.len())
+            .filter_map(|mask| {
+                let mut bits = base;
+                let mut checked = initial_checked;
+                for (k, &(x, y)) in split.iter().enumerate() {
+                    // 高位对应靠前的格子，0 表示勾选
+                    if (mask >> (split.len() - 1 - k)) & 1 == 0 {
+                        bits.set(x, y, true);
+                        checked += 1;
+                    }
+                }
+                if checked > self.max_checked {
+                    self.prune();
+                    return None;
+                }
+                Some((bits, checked, self.clone()))
+            })
+            .collect();
+        info!("并行求解：按前 {} 个非黑格拆分为 {} 个任务", split.len(), tasks.len());
+
+        // 各任务的克隆从拆分前的统计开始计数，结束后把增量加回
+        let (nodes, pruned) = (self.nodes_visited.get(), self.branches_pruned.get());
+        let results: Vec<(Vec<Grid>, u64, u64)> = tasks
+            .into_par_iter()
+            .map(|(bits, checked, solver)| {
+                solver.seen.borrow_mut().clear();
+                let found = RefCell::new(Vec::new());
+                let output = SearchOutput { sink: Sink::Collect(&found), progress: None };
+                run_trampoline(solver.backtrack(start_x, start_y, bits, output, checked, Step::Done));
+                (found.into_inner(), solver.nodes_visited.get() - nodes, solver.branches_pruned.get() - pruned)
+            })
+            .collect();
+        let mut solutions = Vec::new();
+        for (found, task_nodes, task_pruned) in results {
+            solutions.extend(found);
+            self.nodes_visited.set(self.nodes_visited.get() + task_nodes);
+            self.branches_pruned.set(self.branches_pruned.get() + task_pruned);
+        }
+        self.finish_search();
+        self.validate_solutions(solutions)
+    }
+
+    // 回溯结束后独立复核每个解，剔除并报告不合规的解，防止剪枝逻辑的错误产生错误输出
+    pub fn validate_solutions(&self, solutions: Vec<Grid>) -> Vec<Grid> {
+        solutions
+            .into_iter()
+            .filter(|grid| {
+                if let Err(violations) = grid.check_all_rules() {
+                    let reasons: Vec<String> = violations.iter().map(ToString::to_string).collect();
+                    error!("❗ 求解器产生了不合规的解，已剔除（{}）:\n{:?}", reasons.join("；"), grid);
+                    return false;
+                }
+                let ok = grid.check_k_in_a_row(self.config.size)
+                    && grid.checked_count() <= self.max_checked
+                    && self.pre_fixed.iter().all(|&(x, y, checked)| grid.0[x][y].checked == checked);
+                if !ok {
+                    error!("❗ 求解器产生了不合规的解，已剔除:\n{:?}", grid);
+                }
+                ok
+            })
+            .collect()
+    }
+
+    // 蒙特卡洛采样：随机勾选 samples 次，保留满足全部规则的不重复结果。
+    // 网格较大、回溯过慢时，可以用命中率乘以总状态数估计解的数量
+    pub fn sample_monte_carlo(&self, samples: usize, rng: &mut impl Rng) -> MonteCarloEstimate {
+        let mut grid = self.initialize_grid();
+        let mut hits = 0;
+        let mut found: Vec<Grid> = Vec::new();
+        for _ in 0..samples {
+            grid.randomize_unchecked(rng);
+            for &(x, y, checked) in &self.pre_fixed {
+                grid.0[x][y].checked = checked;
+            }
+            if grid.check_rules_by_color(&self.color_groups) && grid.check_k_in_a_row(self.config.size) && grid.checked_count() <= self.max_checked {
+                hits += 1;
+                if !found.iter().any(|s| s.0 == grid.0) {
+                    found.push(grid.clone());
+                }
+            }
+        }
+        let n = self.config.size;
+        let free_cells = (0..n * n).filter(|&k| !self.is_decided(k / n, k % n)).count();
+        MonteCarloEstimate {
+            samples,
+            hits,
+            estimated_solutions: hits as f64 / samples.max(1) as f64 * 2f64.powi(free_cells as i32),
+            found,
+        }
+    }
+
+    // 黑格只能勾选，固定格子只能取指定的状态，其余格子两种取值都可以
+    // 只用到前 size × size 项
+    fn initial_domains(&self) -> [u8; MAX_CELLS] {
+        let mut domains = [CAN_UNCHECK | CAN_CHECK; MAX_CELLS];
+        for (i, row) in self.color_grid.iter().enumerate() {
+            for (j, color) in row.iter().enumerate() {
+                if *color == Color::Black {
+                    domains[i * self.config.size + j] = CAN_CHECK;
+                }
+            }
+        }
+        for &(x, y, checked) in &self.pre_fixed {
+            domains[x * self.config.size + y] &= domain_bit(checked);
+        }
+        domains
+    }
+
+    // line 上的格子全部勾选后，弧相容传播是否仍然没有矛盾（不保证一定有解，但矛盾时一定无解）
+    pub fn line_completeable(&self, line: &[(usize, usize)]) -> bool {
+        let mut grid = self.initialize_grid();
+        let mut domains = self.initial_domains();
+        for &(i, j) in line {
+            domains[i * self.config.size + j] = CAN_CHECK;
+            grid.0[i][j].checked = true;
+        }
+        let all_constraints: Vec<usize> = (0..self.constraints.len()).collect();
+        self.propagate(&mut grid, &mut domains, all_constraints)
+    }
+
+    fn accept_leaf(&self, grid: &Grid, solutions: &mut Vec<Grid>, current_checked: usize) {
+        if grid.check_rules_by_color(&self.color_groups)
+            && grid.check_k_in_a_row(self.config.size)
+            && current_checked <= self.max_checked
+            && !self.is_duplicate(grid)
+        {
+            info!("🎉 找到有效解！总勾选数: {}", current_checked);
+            solutions.push(grid.clone());
+        }
+    }
+
+    // 按行优先顺序逐格赋值，每次赋值后做弧相容传播，只尝试仍在取值域内的值
+    fn backtrack_ac3(
+        &self,
+        index: usize,
+        grid: &mut Grid,
+        domains: &mut [u8; MAX_CELLS],
+        solutions: &mut Vec<Grid>,
+        current_checked: usize,
+        progress: Option<&dyn Fn(u64)>,
+    ) {
+        self.visit_node(progress);
+        if self.out_of_time() {
+            return;
+        }
+        let n = self.config.size;
+        let (x, y) = (index / n, index % n);
+        self.maybe_dump(|| grid.clone(), (x, y));
+
+        if index == n * n {
+            self.accept_leaf(grid, solutions, current_checked);
+            return;
+        }
+
+        let decided = self.is_decided(x, y);
+        let initial = self.color_grid[x][y] == Color::Black || self.fixed_at[index] == Some(true);
+        for checked in [true, false] {
+            if domains[index] & domain_bit(checked) == 0 {
+                continue;
+            }
+            // 黑格与固定格子的勾选已计入初始勾选数
+            let new_checked = current_checked + usize::from(checked && !decided);
+            if new_checked > self.max_checked {
+                self.prune();
+                continue;
+            }
+            let saved = *domains;
+            domains[index] = domain_bit(checked);
+            grid.0[x][y].checked = checked;
+            if self.propagate(grid, domains, self.constraints_of[index].clone()) {
+                self.backtrack_ac3(index + 1, grid, domains, solutions, new_checked, progress);
+            } else {
+                self.prune();
+            }
+            *domains = saved;
+        }
+        grid.0[x][y].checked = initial;
+    }
+
+    // 广义弧相容（AC-3）：对队列中的每条规则，枚举其依赖格子在当前取值域内的所有组合，
+    // 删去在任何满足规则的组合中都不出现的取值；某格取值域被清空说明此分支无解
+    fn propagate(&self, grid: &mut Grid, domains: &mut [u8; MAX_CELLS], mut queue: Vec<usize>) -> bool {
+        let n = self.config.size;
+        while let Some(c) = queue.pop() {
+            let ((x, y), scope) = &self.constraints[c];
+            let saved: Vec<bool> = scope.iter().map(|&v| grid.0[v / n][v % n].checked).collect();
+            let free: Vec<usize> = scope.iter().copied().filter(|&v| domains[v] == CAN_UNCHECK | CAN_CHECK).collect();
+            for &v in scope {
+                if domains[v] != CAN_UNCHECK | CAN_CHECK {
+                    grid.0[v / n][v % n].checked = domains[v] == CAN_CHECK;
+                }
+            }
+
+            let mut supported = [0u8; MAX_CELLS];
+            for mask in 0..1u32 << free.len() {
+                for (bit, &v) in free.iter().enumerate() {
+                    grid.0[v / n][v % n].checked = mask & (1 << bit) != 0;
+                }
+                if grid.check_cell_rule(*x, *y) {
+                    for &v in scope {
+                        supported[v] |= domain_bit(grid.0[v / n][v % n].checked);
+                    }
+                }
+            }
+
+            for (&v, checked) in scope.iter().zip(saved) {
+                grid.0[v / n][v % n].checked = checked;
+            }
+
+            for &v in scope {
+                let narrowed = domains[v] & supported[v];
+                if narrowed == 0 {
+                    return false;
+                }
+                if narrowed != domains[v] {
+                    domains[v] = narrowed;
+                    for &other in &self.constraints_of[v] {
+                        if other != c && !queue.contains(&other) {
+                            queue.push(other);
+                        }
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    // 按勾选状态的哈希去重，均摊 O(1)，不再与已有的解逐个比较
+    fn is_duplicate(&self, grid: &Grid) -> bool {
+        !self.seen.borrow_mut().insert(grid_checked_hash(grid))
+    }
+
+    // 前向检查：在(x,y)处分支之前，判断已经注定无法满足的规则，提前剪掉整棵子树
+    // 只遍历预先找出的红格，其余格子（包括白格、黑格）不参与
+    fn forward_check(&self, bits: BitGrid, current_pos: (usize, usize)) -> bool {
+        for &(i, j) in &self.red_cells {
+            if red_unsatisfiable(bits, i, j, current_pos, self.board_mask) {
+                return false;
+            }
+        }
+        true
+    }
+
+    // 增量校验：刚确定完前 k 个格子时，检查依赖格子已全部确定的规则，不满足时整棵子树都不可能有解
+    fn ready_rules_ok(&self, bits: BitGrid, k: usize) -> bool {
+        self.ready_rules[k]
+            .iter()
+            .all(|&(color, x, y)| profile_rule(color, || bits.check_cell_rule(color, x, y, self.config.size)))
+    }
+
+    // 叶子节点按颜色逐组校验，某一颜色不满足时直接短路
+    fn bit_rules_ok(&self, bits: BitGrid) -> bool {
+        self.color_groups.iter().all(|(color, cells)| {
+            profile_rule(*color, || cells.iter().all(|&(x, y)| bits.check_cell_rule(*color, x, y, self.config.size)))
+        })
+    }
+
+    // 每 DEADLINE_CHECK_INTERVAL 个节点检查一次截止时间，超时后一直返回 true
+    fn out_of_time(&self) -> bool {
+        if self.timed_out.get() {
+            return true;
+        }
+        let Some(deadline) = self.deadline.get() else {
+            return false;
+        };
+        if self.nodes_visited.get().is_multiple_of(DEADLINE_CHECK_INTERVAL) && Instant::now() >= deadline {
+            warn!("求解超过时间限制，已访问 {} 个节点，停止搜索", self.nodes_visited.get());
+            self.timed_out.set(true);
+        }
+        self.timed_out.get()
+    }
+
+    // 节点计数，并按 progress_interval 触发进度回调
+    #[inline]
+    fn visit_node(&self, progress: Option<&dyn Fn(u64)>) {
+        let nodes = self.nodes_visited.get() + 1;
+        self.nodes_visited.set(nodes);
+        if let Some(callback) = progress {
+            if nodes.is_multiple_of(self.progress_interval) {
+                callback(nodes);
+            }
+        }
+    }
+
+    // 在位棋盘上回溯，只有找到解时才转换回 Grid。
+    // 每层不直接递归，而是把"接下来要做什么"打包成 Step 返回，由 run_trampoline 循环驱动：
+    // 勾选分支先行，不勾选分支作为 then 排在它之后，因此调用栈深度始终为常数
+    fn backtrack<'a>(
+        &'a self,
+        x: usize,
+        y: usize,
+        mut bits: BitGrid,
+        output: SearchOutput<'a>,
+        current_checked: usize,
+        then: Step<'a>,
+    ) -> Step<'a> {
+        self.visit_node(output.progress);
+        // 超时：丢弃所有待执行的分支，蹦床随即结束
+        if self.out_of_time() {
+            return Step::Done;
+        }
+        self.maybe_dump(|| bits.to_grid(&self.color_grid), (x, y));
+
+        if self.strategy == SolverStrategy::BacktrackFc
+            && !(self.ready_rules_ok(bits, x * self.config.size + y) && self.forward_check(bits, (x, y)))
+        {
+            self.prune();
+            return then;
+        }
+
+        if x == self.config.size {
+            if self.bit_rules_ok(bits)
+                && bits.has_full_line(&self.line_masks)
+                && current_checked <= self.max_checked
+            {
+                match output.sink {
+                    Sink::Collect(solutions) => {
+                        let grid = bits.to_grid(&self.color_grid);
+                        if !self.is_duplicate(&grid) {
+                            info!("🎉 找到有效解！总勾选数: {}", current_checked);
+                            solutions.borrow_mut().push(grid);
+                        }
+                    }
+                    // 每个叶子对应一种不同的勾选组合，计数时不需要去重
+                    Sink::Count(count) => count.set(count.get() + 1),
+                }
+            }
+            return then;
+        }
+    
+        let (next_x, next_y) = self.next_position(x, y);
+        
+        if self.is_decided(x, y) {
+            return Step::Continue(Box::new(move || self.backtrack(next_x, next_y, bits, output, current_checked, then)));
+        }
+
+        // 回溯，尝试不勾选（排在勾选分支之后执行）
+        bits.set(x, y, false);
+        let unchecked = Step::Continue(Box::new(move || self.backtrack(next_x, next_y, bits, output, current_checked, then)));
+
+        // 尝试勾选该单元格，仅保留总勾选数剪枝
+        bits.set(x, y, true);
+        let new_checked = current_checked + 1;
+        if new_checked > self.max_checked {
+            self.prune();
+            return unchecked;
+        }
+        Step::Continue(Box::new(move || self.backtrack(next_x, next_y, bits, output, new_checked, unchecked)))
+    }
+}
+
+// 回溯各层共享的输出：解的去向，以及可选的进度回调
+#[derive(Clone, Copy)]
+struct SearchOutput<'a> {
+    sink: Sink<'a>,
+    progress: Option<&'a dyn Fn(u64)>,
+}
+
+// 叶子节点找到解时：收集完整的 Grid，或者只计数（不构造 Grid）
+#[derive(Clone, Copy)]
+enum Sink<'a> {
+    Collect(&'a RefCell<Vec<Grid>>),
+    Count(&'a CounterCell<usize>),
+}
+
+// ----------------------------- 流式求解 -----------------------------
+// 用显式栈代替递归的回溯：栈中每项为待访问的节点（已确定的格子数、位棋盘、勾选数），
+// 不勾选的分支先入栈、勾选的分支后入栈，因此与 backtrack 一样先探索勾选分支
+pub struct SolverIter<'a> {
+    solver: &'a Solver,
+    stack: Vec<(usize, BitGrid, usize)>,
+}
+
+impl Iterator for SolverIter<'_> {
+    type Item = Grid;
+
+    fn next(&mut self) -> Option<Grid> {
+        let solver = self.solver;
+        let n = solver.config.size;
+        while let Some((k, mut bits, current_checked)) = self.stack.pop() {
+            solver.visit_node(None);
+            let (x, y) = (k / n, k % n);
+            if solver.strategy != SolverStrategy::Backtrack
+                && !(solver.ready_rules_ok(bits, k) && solver.forward_check(bits, (x, y)))
+            {
+                solver.prune();
+                continue;
+            }
+
+            if k == n * n {
+                if solver.bit_rules_ok(bits) && bits.has_full_line(&solver.line_masks) && current_checked <= solver.max_checked {
+                    info!("🎉 找到有效解！总勾选数: {}", current_checked);
+                    solver.finish_search();
+                    return Some(bits.to_grid(&solver.color_grid));
+                }
+                continue;
+            }
+
+            if solver.is_decided(x, y) {
+                self.stack.push((k + 1, bits, current_checked));
+                continue;
+            }
+            bits.set(x, y, false);
+            self.stack.push((k + 1, bits, current_checked));
+            if current_checked < solver.max_checked {
+                bits.set(x, y, true);
+                self.stack.push((k + 1, bits, current_checked + 1));
+            } else {
+                solver.prune();
+            }
+        }
+        solver.finish_search();
+        None
+    }
+}
+
+// ----------------------------- 蹦床 -----------------------------
+// 回溯的每一步返回下一步要执行的闭包，Done 表示整棵搜索树已经走完
+enum Step<'a> {
+    Done,
+    Continue(Box<dyn FnOnce() -> Step<'a> + 'a>),
+}
+
+fn run_trampoline(mut step: Step<'_>) {
+    while let Step::Continue(next) = step {
+        step = next();
+    }
+}
+
+// ----------------------------- 解去重 -----------------------------
+// 64 位 FNV-1a：按行优先顺序把每个格子的勾选状态（0 或 1）作为一个字节喂进去，不同尺寸的网格互不冲突
+pub fn grid_checked_hash(grid: &Grid) -> u64 {
+    grid.0.iter().flatten().fold(0xcbf2_9ce4_8422_2325, |hash, cell| {
+        (hash ^ u64::from(cell.checked)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+// 按 grids_are_symmetric 分类，每类保留顺序最靠前的一个，其余顺序不变
+pub fn canonical_solutions(solutions: Vec<Grid>) -> Vec<Grid> {
+    let mut kept: Vec<Grid> = Vec::new();
+    for solution in solutions {
+        if !kept.iter().any(|other| grids_are_symmetric(other, &solution)) {
+            kept.push(solution);
+        }
+    }
+    kept
+}
+
+// 未指定 max_checked 时的默认上限：黑格数 + 凑成一条线至少还要勾的格子数 + 余量。
+// 余量取格子总数的三分之二（5 × 5 时为 16）：data/ 中约 500 道历史题目里，
+// 解的勾选数比前两项之和最多多出 16，默认上限因此不会漏掉这些题目的任何解，只剪掉勾选多得离谱的分支
+pub fn auto_max_checked(color_grid: &[Vec<Color>]) -> usize {
+    let grid = Grid::new_blank(color_grid);
+    let cells = color_grid.iter().map(Vec::len).sum::<usize>();
+    let blacks = color_grid.iter().flatten().filter(|&&color| color == Color::Black).count();
+    let line_cost = grid
+        .iter_lines()
+        .map(|line| line.iter().filter(|&&(i, j)| color_grid[i][j] != Color::Black).count())
+        .min()
+        .unwrap_or(0);
+    (blacks + line_cost + cells * 2 / 3).min(cells)
+}
+
+// 红格前瞻：按行优先顺序，current_pos 之前的格子都已确定。
+// 若红格的邻居没有一个被勾选，且全部都已确定（之后的黑格在初始化时已勾选，其余格子还可能被勾选），
+// 则该红格永远无法满足。board 为网格内格子的掩码（bitgrid::board_mask），网格外的位视为已确定
+pub fn red_unsatisfiable(bits: BitGrid, x: usize, y: usize, current_pos: (usize, usize), board: u64) -> bool {
+    let neighbors = NEIGHBOR_MASKS[x * MAX_GRID_SIZE + y];
+    let position = current_pos.0 * MAX_GRID_SIZE + current_pos.1;
+    let decided = 1u64.checked_shl(position as u32).map_or(u64::MAX, |b| b - 1) | !board;
+    bits.0 & neighbors == 0 && neighbors & !decided == 0
+}
+
+// 求解前的 O(格子数) 快速检查，只排除"无论怎样勾选都不可能满足"的布局：
+// - 黑格数量超过 max_checked（黑格必须勾）
+// - 蓝格周围黑格超过两个
+// - 紫格/橙格周围全是黑格且黑格数量奇偶不对
+// - 黄格两条对角线上除自身外全是黑格且两边数量不等
+// - 粉格周围有黑格
+// 红格周围要么有可勾选的格子，要么全是已勾的黑格，永远可满足；绿格、碧格、青格、灰格、栗格、藏格也无法仅凭颜色排除
+pub fn is_satisfiable_quick(color_grid: &[Vec<Color>], max_checked: usize) -> bool {
+    let grid = Grid::new_blank(color_grid);
+    let is_black = |(i, j): &(usize, usize)| color_grid[*i][*j] == Color::Black;
+
+    let black_count = color_grid.iter().flatten().filter(|c| **c == Color::Black).count();
+    if black_count > max_checked {
+        return false;
+    }
+
+    for (x, row) in color_grid.iter().enumerate() {
+        for (y, &color) in row.iter().enumerate() {
+            let neighbors = grid.get_neighbors(x, y);
+            let black_neighbors = neighbors.iter().filter(|p| is_black(p)).count();
+            let all_black = black_neighbors == neighbors.len();
+            let ok = match color {
+                Color::Blue => black_neighbors <= 2,
+                Color::Pink => black_neighbors == 0,
+                Color::Purple => !all_black || black_neighbors % 2 == 1,
+                Color::Orange => !all_black || black_neighbors.is_multiple_of(2),
+                Color::Yellow => {
+                    // 黄格自身同时位于两条对角线上，勾不勾都不影响两边之差；只需比较其余格子
+                    let mut diag1 = grid.get_diagonal(x, y, (-1, -1), (1, 1));
+                    diag1.retain(|p| *p != (x, y));
+                    let mut diag2 = grid.get_diagonal(x, y, (-1, 1), (1, -1));
+                    diag2.retain(|p| *p != (x, y));
+                    let fixed = |d: &[(usize, usize)]| d.iter().all(is_black);
+                    !(fixed(&diag1) && fixed(&diag2)) || diag1.len() == diag2.len()
+                }
+                _ => true,
+            };
+            if !ok {
+                debug!("❌ 快速检查：{}格({},{})不可能满足", color.label(), x, y);
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 左上角是粉格，其余为白格
+    const PINK_CORNER: &str = "IWW\nWWW\nWWW";
+
+    fn grid_with(layout: &str, checked: &[(usize, usize)]) -> Grid {
+        let mut grid = Grid::from_string(layout).unwrap();
+        for &(x, y) in checked {
+            grid.0[x][y].checked = true;
+        }
+        grid
+    }
+
+    fn color_grid(layout: &str) -> Vec<Vec<Color>> {
+        let grid = Grid::from_string(layout).unwrap();
+        grid.0.iter().map(|row| row.iter().map(|cell| cell.color).collect()).collect()
+    }
+
+    #[test]
+    fn validate_solutions_drops_injected_invalid_solutions() {
+        let solver = Solver::builder(color_grid(PINK_CORNER)).max_checked(3).build().unwrap();
+        // 不碰粉格周围三格的线只有第 2 行和第 2 列
+        let solutions = solver.solve();
+        assert_eq!(solutions.len(), 2);
+
+        let mut injected = solutions.clone();
+        injected.push(grid_with(PINK_CORNER, &[(1, 0), (1, 1), (1, 2)])); // 粉格周围有勾选
+        injected.push(grid_with(PINK_CORNER, &[(2, 0), (2, 1)])); // 没有连成一条线
+        injected.push(grid_with(PINK_CORNER, &[(2, 0), (2, 1), (2, 2), (0, 2)])); // 超过 max_checked
+        assert_eq!(solver.validate_solutions(injected), solutions);
+
+        // 与固定格子不一致的解同样剔除
+        let fixed = solver.clone().with_fixed_cells(vec![(2, 0, true)]);
+        let kept = fixed.validate_solutions(solutions);
+        assert_eq!(kept, vec![grid_with(PINK_CORNER, &[(2, 0), (2, 1), (2, 2)])]);
+    }
+
+    // 3 × 3 网格的全部 512 种勾选组合
+    fn all_checkings(layout: &str) -> Vec<Grid> {
+        let blank = Grid::from_string(layout).unwrap();
+        (0u32..1 << 9)
+            .map(|mask| {
+                let mut grid = blank.clone();
+                for k in 0..9 {
+                    grid.0[k / 3][k % 3].checked = mask & (1 << k) != 0;
+                }
+                grid
+            })
+            .collect()
+    }
+
+    // 穷举得到的全部解，不经过求解器的去重
+    fn brute_force_solutions(layout: &str, max_checked: usize) -> Vec<Grid> {
+        all_checkings(layout)
+            .into_iter()
+            .filter(|grid| {
+                grid.0.iter().flatten().all(|cell| cell.checked || cell.color != Color::Black)
+                    && grid.check_all_rules().is_ok()
+                    && grid.check_k_in_a_row(3)
+                    && grid.checked_count() <= max_checked
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dedup_never_drops_a_distinct_solution() {
+        // 四种布局分别有 282、13、17、51 个解
+        for layout in ["WWW\nWWW\nWWW", "RBW\nWKP\nOWC", "WGW\nYWT\nMWA", "KWW\nWOW\nWWK"] {
+            let expected: HashSet<u64> = brute_force_solutions(layout, 9).iter().map(grid_checked_hash).collect();
+            for strategy in [SolverStrategy::Backtrack, SolverStrategy::BacktrackFc, SolverStrategy::BacktrackAc3] {
+                let solver = Solver::builder(color_grid(layout)).max_checked(9).strategy(strategy).build().unwrap();
+                let solutions = solver.solve();
+                let found: HashSet<u64> = solutions.iter().map(grid_checked_hash).collect();
+                assert_eq!(found.len(), solutions.len(), "{} 返回了重复的解", strategy.name());
+                assert_eq!(found, expected, "{} 在布局\n{}\n上漏掉或多出了解", strategy.name(), layout);
+            }
+        }
+    }
+
+    #[test]
+    fn distinct_grids_are_never_reported_as_duplicates() {
+        let layout = "WWW\nWWW\nWWW";
+        let solver = Solver::builder(color_grid(layout)).build().unwrap();
+        let all = all_checkings(layout);
+        assert!(all.iter().all(|grid| !solver.is_duplicate(grid)));
+        assert!(all.iter().all(|grid| solver.is_duplicate(grid)));
+    }
+
+    #[test]
+    fn two_paths_to_the_same_solution_hash_the_same() {
+        let layout = "RBKGY\nPWOCT\nIAMNW\nWWKWW\nRRBBW";
+        let line = [(3, 0), (3, 1), (3, 2), (3, 3), (3, 4)];
+
+        // 按不同顺序勾选，途中还勾过又取消了一个格子
+        let mut forward = Grid::from_string(layout).unwrap();
+        for &(x, y) in &line {
+            forward.0[x][y].checked = true;
+        }
+        let mut backward = Grid::from_string(layout).unwrap();
+        backward.0[0][0].checked = true;
+        for &(x, y) in line.iter().rev() {
+            backward.0[x][y].checked = true;
+        }
+        backward.0[0][0].checked = false;
+        // 求解器回溯时从位棋盘还原网格
+        let from_bits = BitGrid::from_grid(&forward).to_grid(&color_grid(layout));
+
+        assert_eq!(grid_checked_hash(&forward), grid_checked_hash(&backward));
+        assert_eq!(grid_checked_hash(&forward), grid_checked_hash(&from_bits));
+        let solver = Solver::builder(color_grid(layout)).build().unwrap();
+        assert!(!solver.is_duplicate(&forward));
+        assert!(solver.is_duplicate(&backward));
+        assert!(solver.is_duplicate(&from_bits));
+
+        // 多勾一格就是另一个解；尺寸不同的空白网格也互不冲突
+        backward.0[4][4].checked = true;
+        assert_ne!(grid_checked_hash(&forward), grid_checked_hash(&backward));
+        assert!(!solver.is_duplicate(&backward));
+        let blank_4 = Grid::from_string("WWWW\nWWWW\nWWWW\nWWWW").unwrap();
+        let blank_5 = Grid::from_string("WWWWW\nWWWWW\nWWWWW\nWWWWW\nWWWWW").unwrap();
+        assert_ne!(grid_checked_hash(&blank_4), grid_checked_hash(&blank_5));
+    }
+
+    #[test]
+    fn build_rejects_malformed_color_grids() {
+        let bad_shapes = [
+            vec![vec![Color::Red], vec![Color::Red, Color::Blue]],
+            Vec::new(),
+            vec![vec![Color::White; MAX_GRID_SIZE + 1]; MAX_GRID_SIZE + 1],
+        ];
+        for color_grid in bad_shapes {
+            let shape: Vec<usize> = color_grid.iter().map(Vec::len).collect();
+            assert!(matches!(Solver::builder(color_grid).build(), Err(BingoError::InvalidInput(_))), "{:?}", shape);
+        }
+        // 与 grid_config 指定的尺寸不一致
+        let result = Solver::new(color_grid("WWW\nWWW\nWWW"), GridConfig::new(4).unwrap());
+        assert!(matches!(result, Err(BingoError::InvalidInput(_))));
+    }
+}
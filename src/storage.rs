@@ -0,0 +1,518 @@
+use std::{fs, path::{Path, PathBuf}};
+#[cfg(feature = "serde")]
+use std::io::{Read, Write};
+
+#[cfg(feature = "serde")]
+use flate2::read::GzDecoder;
+use flate2::{write::GzEncoder, Compression};
+
+use crate::error::BingoError;
+#[cfg(feature = "serde")]
+use crate::config::MAX_GRID_SIZE;
+use crate::difficulty::{classify_difficulty, solutions_difficulty};
+#[cfg(feature = "serde")]
+use crate::grid::Color;
+use crate::grid::Grid;
+
+// ----------------------------- 输出函数 -----------------------------
+// compress 为 true 时写入 path + ".gz"（gzip 压缩），否则写入纯文本 JSON；
+// 同时删除另一种格式的旧文件，免得读取时读到上一次运行留下的结果
+#[cfg(feature = "serde")]
+pub fn save_solutions_json(solutions: &[Grid], path: &str, compress: bool) -> Result<(), BingoError> {
+    let data = solutions_json(solutions);
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let gz_path = format!("{}.gz", path);
+    let stale = if compress {
+        let mut encoder = GzEncoder::new(fs::File::create(&gz_path)?, Compression::default());
+        encoder.write_all(data.as_bytes())?;
+        encoder.finish()?;
+        path
+    } else {
+        fs::write(path, data)?;
+        &gz_path
+    };
+    if Path::new(stale).exists() {
+        fs::remove_file(stale)?;
+    }
+    Ok(())
+}
+
+// difficulty 为 puzzle_difficulty 的评分（见 solutions_difficulty），difficulty_tier 为其所属档位；
+// fingerprints 与 solutions 一一对应（见 Grid::solution_fingerprint），比较两个解时不必逐格对比。
+// 单独成一个数组而不是放进每个解里，旧的读取方（按网格解析 solutions）不受影响
+#[cfg(feature = "serde")]
+pub fn solutions_json(solutions: &[Grid]) -> String {
+    let difficulty = solutions_difficulty(solutions);
+    serde_json::json!({
+        "solutions": solutions,
+        "fingerprints": solutions.iter().map(Grid::solution_fingerprint).collect::<Vec<_>>(),
+        "total_solutions": solutions.len(),
+        "difficulty": difficulty,
+        "difficulty_tier": classify_difficulty(difficulty),
+    })
+    .to_string()
+}
+
+// ----------------------------- JSON 格式说明 -----------------------------
+// solutions_json 输出格式的 JSON Schema（draft-07），供网页、App 等下游校验自己的解析逻辑
+#[cfg(feature = "serde")]
+pub fn solutions_json_schema() -> serde_json::Value {
+    let color_names: Vec<String> = Color::ALL.iter().map(|color| format!("{:?}", color)).collect();
+    serde_json::json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Daily Bingo solutions",
+        "type": "object",
+        "required": ["solutions", "total_solutions", "difficulty", "difficulty_tier"],
+        "properties": {
+            "solutions": {
+                "type": "array",
+                "items": { "$ref": "#/definitions/grid" }
+            },
+            "fingerprints": {
+                "description": "与 solutions 一一对应，第 x * n + y 位表示 (x, y) 是否勾选；8 × 8 网格的指纹可能超出 2^53，JavaScript 中须按 BigInt 解析",
+                "type": "array",
+                "items": { "type": "integer", "minimum": 0 }
+            },
+            "total_solutions": { "type": "integer", "minimum": 0 },
+            "difficulty": { "type": "number", "minimum": 0.0, "maximum": 1.0 },
+            "difficulty_tier": { "type": "string", "enum": ["Easy", "Medium", "Hard", "Extreme"] }
+        },
+        "definitions": {
+            "grid": {
+                "description": "按行排列的方形网格，第 x 行第 y 列的格子坐标为 (x, y)",
+                "type": "array",
+                "minItems": 1,
+                "maxItems": MAX_GRID_SIZE,
+                "items": {
+                    "type": "array",
+                    "minItems": 1,
+                    "maxItems": MAX_GRID_SIZE,
+                    "items": { "$ref": "#/definitions/cell" }
+                }
+            },
+            "cell": {
+                "type": "object",
+                "required": ["x", "y", "color", "checked"],
+                "additionalProperties": false,
+                "properties": {
+                    "x": { "type": "integer", "minimum": 0, "maximum": MAX_GRID_SIZE - 1 },
+                    "y": { "type": "integer", "minimum": 0, "maximum": MAX_GRID_SIZE - 1 },
+                    "color": { "type": "string", "enum": color_names },
+                    "checked": { "type": "boolean" }
+                }
+            }
+        }
+    })
+}
+
+// 按 solutions_json_schema 的约定逐项检查，另外检查 Schema 表达不了的部分：
+// 网格须为方形、格子坐标与所在位置一致、total_solutions 与解的个数相等。返回全部不符合之处
+#[cfg(feature = "serde")]
+pub fn validate_solutions_json(value: &serde_json::Value) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+    let Some(object) = value.as_object() else {
+        return Err(vec!["顶层应为对象".to_string()]);
+    };
+
+    let solutions = object.get("solutions").and_then(|v| v.as_array());
+    match solutions {
+        Some(solutions) => {
+            for (i, grid) in solutions.iter().enumerate() {
+                validate_grid_json(grid, &format!("solutions[{}]", i), &mut errors);
+            }
+        }
+        None => errors.push("缺少数组字段 solutions".to_string()),
+    }
+
+    // fingerprints 是后来加入的字段，旧文件中可以没有
+    if let Some(fingerprints) = object.get("fingerprints") {
+        validate_fingerprints_json(fingerprints, solutions.map_or(&[], Vec::as_slice), &mut errors);
+    }
+
+    match object.get("total_solutions").and_then(|v| v.as_u64()) {
+        Some(total) => {
+            if let Some(solutions) = solutions {
+                if total != solutions.len() as u64 {
+                    errors.push(format!("total_solutions 为 {}，但 solutions 中有 {} 个解", total, solutions.len()));
+                }
+            }
+        }
+        None => errors.push("缺少非负整数字段 total_solutions".to_string()),
+    }
+
+    match object.get("difficulty").and_then(|v| v.as_f64()) {
+        Some(difficulty) if (0.0..=1.0).contains(&difficulty) => {}
+        Some(difficulty) => errors.push(format!("difficulty 应在 0.0 ~ 1.0 之间，实际为 {}", difficulty)),
+        None => errors.push("缺少数值字段 difficulty".to_string()),
+    }
+
+    match object.get("difficulty_tier").and_then(|v| v.as_str()) {
+        Some("Easy" | "Medium" | "Hard" | "Extreme") => {}
+        Some(tier) => errors.push(format!("difficulty_tier 的值 {} 不是已知档位", tier)),
+        None => errors.push("缺少字符串字段 difficulty_tier".to_string()),
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// 个数须与解相同；解本身格式正确时，指纹还须与其勾选状态一致
+#[cfg(feature = "serde")]
+fn validate_fingerprints_json(fingerprints: &serde_json::Value, solutions: &[serde_json::Value], errors: &mut Vec<String>) {
+    let Some(fingerprints) = fingerprints.as_array() else {
+        errors.push("fingerprints 应为数组".to_string());
+        return;
+    };
+    if fingerprints.len() != solutions.len() {
+        errors.push(format!("fingerprints 有 {} 项，但 solutions 中有 {} 个解", fingerprints.len(), solutions.len()));
+    }
+    for (i, (fingerprint, grid)) in fingerprints.iter().zip(solutions).enumerate() {
+        let Some(fingerprint) = fingerprint.as_u64() else {
+            errors.push(format!("fingerprints[{}] 应为非负整数", i));
+            continue;
+        };
+        if let Ok(grid) = serde_json::from_value::<Grid>(grid.clone()) {
+            if grid.solution_fingerprint() != fingerprint {
+                errors.push(format!("fingerprints[{}] 为 {}，与 solutions[{}] 的勾选状态不符", i, fingerprint, i));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn validate_grid_json(grid: &serde_json::Value, at: &str, errors: &mut Vec<String>) {
+    let Some(rows) = grid.as_array() else {
+        errors.push(format!("{} 应为数组", at));
+        return;
+    };
+    let n = rows.len();
+    if !(1..=MAX_GRID_SIZE).contains(&n) {
+        errors.push(format!("{} 的行数应在 1..={} 之间，实际为 {}", at, MAX_GRID_SIZE, n));
+    }
+    for (x, row) in rows.iter().enumerate() {
+        let Some(cells) = row.as_array() else {
+            errors.push(format!("{}[{}] 应为数组", at, x));
+            continue;
+        };
+        if cells.len() != n {
+            errors.push(format!("{}[{}] 应有 {} 个格子，实际为 {}", at, x, n, cells.len()));
+        }
+        for (y, cell) in cells.iter().enumerate() {
+            validate_cell_json(cell, (x, y), &format!("{}[{}][{}]", at, x, y), errors);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn validate_cell_json(cell: &serde_json::Value, (x, y): (usize, usize), at: &str, errors: &mut Vec<String>) {
+    let Some(fields) = cell.as_object() else {
+        errors.push(format!("{} 应为对象", at));
+        return;
+    };
+    for (key, expected) in [("x", x), ("y", y)] {
+        match fields.get(key).and_then(|v| v.as_u64()) {
+            Some(found) if found == expected as u64 => {}
+            Some(found) => errors.push(format!("{}.{} 应为 {}，实际为 {}", at, key, expected, found)),
+            None => errors.push(format!("{} 缺少非负整数字段 {}", at, key)),
+        }
+    }
+    match fields.get("color").and_then(|v| v.as_str()) {
+        Some(name) if Color::ALL.iter().any(|color| format!("{:?}", color) == name) => {}
+        Some(name) => errors.push(format!("{}.color 的值 {} 不是已知颜色", at, name)),
+        None => errors.push(format!("{} 缺少字符串字段 color", at)),
+    }
+    if !fields.get("checked").is_some_and(|v| v.is_boolean()) {
+        errors.push(format!("{} 缺少布尔字段 checked", at));
+    }
+    for key in fields.keys() {
+        if !["x", "y", "color", "checked"].contains(&key.as_str()) {
+            errors.push(format!("{} 含有未知字段 {}", at, key));
+        }
+    }
+}
+
+// 纯文本的解列表，每个解一行：编号、勾选格子坐标、勾选数、难度，各列用空格对齐。
+// 难度是整道题的档位（与 solutions.json 的 difficulty_tier 相同），每行都一样
+pub fn format_solutions_table(solutions: &[Grid]) -> String {
+    let tier = classify_difficulty(solutions_difficulty(solutions));
+    let rows: Vec<[String; 4]> = solutions
+        .iter()
+        .enumerate()
+        .map(|(i, solution)| {
+            let cells: Vec<String> = solution.0
+                .iter()
+                .flatten()
+                .filter(|cell| cell.checked)
+                .map(|cell| format!("({},{})", cell.x, cell.y))
+                .collect();
+            [
+                format!("Sol {}", i + 1),
+                cells.join(","),
+                format!("{} checked", solution.checked_count()),
+                tier.to_string(),
+            ]
+        })
+        .collect();
+
+    let mut widths = [0; 4];
+    for row in &rows {
+        for (width, column) in widths.iter_mut().zip(row) {
+            *width = (*width).max(column.len());
+        }
+    }
+    rows.iter()
+        .map(|row| {
+            let columns: Vec<String> = row
+                .iter()
+                .zip(widths)
+                .map(|(column, width)| format!("{:<width$}", column, width = width))
+                .collect();
+            format!("| {} |", columns.join(" | "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// 按 solutions_json_file 的顺序读取 path + ".gz" 或纯文本的 path
+#[cfg(feature = "serde")]
+pub fn load_solutions_json(path: &str) -> Result<Vec<Grid>, BingoError> {
+    let text = match solutions_json_file(Path::new(path)) {
+        Some((gz_path, true)) => {
+            let mut text = String::new();
+            GzDecoder::new(fs::File::open(gz_path)?).read_to_string(&mut text)?;
+            text
+        }
+        Some((path, false)) => fs::read_to_string(path)?,
+        None => fs::read_to_string(path)?, // 两者都不存在，返回 NotFound
+    };
+    parse_solutions_json(&text)
+}
+
+// path 为 solutions.json 的路径，返回实际存在的文件及其是否为 gzip 压缩：先找 path + ".gz"，再找 path。
+// 读取求解结果的地方（load_solutions_json、date_folder_solved、HTTP 服务）都按这个顺序
+pub fn solutions_json_file(path: &Path) -> Option<(PathBuf, bool)> {
+    let mut gz_path = path.as_os_str().to_owned();
+    gz_path.push(".gz");
+    let gz_path = PathBuf::from(gz_path);
+    if gz_path.is_file() {
+        Some((gz_path, true))
+    } else if path.is_file() {
+        Some((path.to_path_buf(), false))
+    } else {
+        None
+    }
+}
+
+#[cfg(feature = "serde")]
+pub fn parse_solutions_json(text: &str) -> Result<Vec<Grid>, BingoError> {
+    let mut data: serde_json::Value = serde_json::from_str(text)?;
+    let solutions = serde_json::from_value(data["solutions"].take())?;
+    Ok(solutions)
+}
+
+// 把当天的所有产物打包写入 <root>/<date>.tar.gz，替代 <root>/<date>/ 文件夹（root 通常为 data）
+pub fn save_date_tarball(root: &str, date: &str, files: &[(String, Vec<u8>)]) -> Result<PathBuf, BingoError> {
+    let path = PathBuf::from(format!("{}/{}.tar.gz", root, date));
+    let encoder = GzEncoder::new(fs::File::create(&path)?, Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+    for (name, bytes) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(chrono::Utc::now().timestamp() as u64);
+        builder.append_data(&mut header, name, bytes.as_slice())?;
+    }
+    builder.into_inner()?.finish()?;
+    Ok(path)
+}
+
+// 文件名形如 2025-01-01.tar.gz（save_date_tarball 的输出）
+fn is_date_tarball(file_name: &str) -> bool {
+    file_name
+        .strip_suffix(".tar.gz")
+        .is_some_and(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok())
+}
+
+pub fn date_folder_exists(root: &str, date: &str) -> bool {
+    Path::new(&format!("{}/{}", root, date)).is_dir()
+}
+
+// 当天的文件夹里已经有求解结果（solutions.json 或其 .gz）
+pub fn date_folder_solved(root: &str, date: &str) -> bool {
+    solutions_json_file(Path::new(&format!("{}/{}/solutions.json", root, date))).is_some()
+}
+
+// 日期文件夹已存在时，overwrite 为 true 则整个删除后重建，否则返回 DateFolderExists，不做任何改动
+pub fn move_to_date_folder(root: &str, date: &str, overwrite: bool) -> Result<(), BingoError> {
+    let date_folder = format!("{}/{}", root, date);
+    if date_folder_exists(root, date) {
+        if !overwrite {
+            return Err(BingoError::DateFolderExists(date_folder));
+        }
+        fs::remove_dir_all(&date_folder)?;
+    }
+    fs::create_dir_all(&date_folder)?;
+
+    // 需要保留在根目录的文件名（index.json、archive.json 是跨日期的汇总，不属于某一天）
+    let keep_files = ["solutions.json", "solutions.json.gz", "blank.png", "index.json", "archive.json"];
+
+    for entry in fs::read_dir(root)? {
+        let entry = entry?;
+        let path = entry.path();
+
+        // 仅处理文件，跳过目录
+        if !path.is_file() {
+            continue;
+        }
+
+        // 非 UTF-8 的文件名不会是本程序的产物，原样留在根目录
+        let file_name = entry.file_name();
+        let Some(file_name_str) = file_name.to_str() else {
+            continue;
+        };
+
+        // 仅移动非保留文件（且不是当前日期的文件夹）；
+        // save_date_tarball 打出的 <日期>.tar.gz 同样留在根目录，PuzzleArchive 在那里查找它们
+        if !keep_files.contains(&file_name_str) && file_name_str != date && !is_date_tarball(file_name_str) {
+            let new_path = format!("{}/{}", date_folder, file_name_str);
+            fs::rename(&path, new_path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 每个测试在系统临时目录下使用各自的子目录，测试之间互不影响
+    fn temp_root(name: &str) -> String {
+        let root = std::env::temp_dir().join(format!("bingo-storage-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        root.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn move_to_date_folder_keeps_date_tarballs_in_root() {
+        let root = temp_root("tarball");
+        fs::write(format!("{}/2025-01-01.tar.gz", root), b"old").unwrap();
+        fs::write(format!("{}/solution_1.png", root), b"png").unwrap();
+
+        move_to_date_folder(&root, "2025-01-02", false).unwrap();
+
+        assert!(Path::new(&format!("{}/2025-01-01.tar.gz", root)).is_file());
+        assert!(Path::new(&format!("{}/2025-01-02/solution_1.png", root)).is_file());
+        assert!(!Path::new(&format!("{}/2025-01-02/2025-01-01.tar.gz", root)).exists());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn saving_one_format_removes_the_other() {
+        let root = temp_root("switch");
+        let path = format!("{}/solutions.json", root);
+        let compressed = Grid::from_string("RB\nGW").unwrap();
+        let plain = Grid::from_string("KY\nPO").unwrap();
+
+        save_solutions_json(&[compressed], &path, true).unwrap();
+        save_solutions_json(std::slice::from_ref(&plain), &path, false).unwrap();
+        assert!(!Path::new(&format!("{}.gz", path)).exists());
+        assert_eq!(load_solutions_json(&path).unwrap(), vec![plain.clone()]);
+
+        save_solutions_json(std::slice::from_ref(&plain), &path, true).unwrap();
+        assert!(!Path::new(&path).exists());
+        assert_eq!(solutions_json_file(Path::new(&path)), Some((PathBuf::from(format!("{}.gz", path)), true)));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn solutions_table_uses_the_json_difficulty_tier() {
+        let mut solution = Grid::from_string("RBW\nGYW\nKPW").unwrap();
+        for cell in &mut solution.0[2] {
+            cell.checked = true;
+        }
+        let json: serde_json::Value = serde_json::from_str(&solutions_json(std::slice::from_ref(&solution))).unwrap();
+        let tier = json["difficulty_tier"].as_str().unwrap();
+        let table = format_solutions_table(&[solution]);
+        assert!(table.trim_end_matches(" |").ends_with(tier), "{}", table);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn compressed_solutions_round_trip() {
+        let root = temp_root("gzip");
+        let path = format!("{}/solutions.json", root);
+        let mut solution = Grid::from_string("RBG\nWYK\nPOC").unwrap();
+        solution.0[1][2].checked = true;
+        solution.0[0][0].checked = true;
+        let solutions = vec![solution.clone(), Grid::new_blank(&[vec![Color::White; 3], vec![Color::Red; 3], vec![Color::Blue; 3]])];
+
+        save_solutions_json(&solutions, &path, true).unwrap();
+        let bytes = fs::read(format!("{}.gz", path)).unwrap();
+        assert_eq!(&bytes[..2], &[0x1f, 0x8b], "应为 gzip 数据");
+        assert!(!Path::new(&path).exists());
+        assert_eq!(load_solutions_json(&path).unwrap(), solutions);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    fn sample_solutions_json() -> serde_json::Value {
+        let layout = Grid::from_string("WGW\nYWT\nMWA").unwrap();
+        let color_grid: Vec<Vec<Color>> = layout.0.iter().map(|row| row.iter().map(|cell| cell.color).collect()).collect();
+        let solutions = crate::solver::Solver::builder(color_grid).max_checked(9).build().unwrap().solve();
+        assert!(!solutions.is_empty());
+        serde_json::from_str(&solutions_json(&solutions)).unwrap()
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn solutions_json_output_passes_validation() {
+        assert_eq!(validate_solutions_json(&sample_solutions_json()), Ok(()));
+        let empty: serde_json::Value = serde_json::from_str(&solutions_json(&[])).unwrap();
+        assert_eq!(validate_solutions_json(&empty), Ok(()));
+
+        // 旧文件没有 fingerprints 字段
+        let mut old = sample_solutions_json();
+        old.as_object_mut().unwrap().remove("fingerprints");
+        assert_eq!(validate_solutions_json(&old), Ok(()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn validation_reports_every_malformed_field() {
+        assert_eq!(validate_solutions_json(&serde_json::json!([])), Err(vec!["顶层应为对象".to_string()]));
+
+        type Mutation = fn(&mut serde_json::Value);
+        let mutations: [(&str, Mutation); 8] = [
+            ("total_solutions 为", |v| v["total_solutions"] = serde_json::json!(99)),
+            ("difficulty 应在", |v| v["difficulty"] = serde_json::json!(1.5)),
+            ("不是已知档位", |v| v["difficulty_tier"] = serde_json::json!("Trivial")),
+            ("不是已知颜色", |v| v["solutions"][0][1][1]["color"] = serde_json::json!("Magenta")),
+            ("solutions[0][2][0].x 应为 2", |v| v["solutions"][0][2][0]["x"] = serde_json::json!(0)),
+            ("应有 3 个格子", |v| {
+                v["solutions"][0][1].as_array_mut().unwrap().pop();
+            }),
+            ("含有未知字段 note", |v| v["solutions"][0][0][0]["note"] = serde_json::json!("")),
+            ("与 solutions[0] 的勾选状态不符", |v| v["fingerprints"][0] = serde_json::json!(0)),
+        ];
+        for (expected, mutate) in mutations {
+            let mut value = sample_solutions_json();
+            mutate(&mut value);
+            let errors = validate_solutions_json(&value).unwrap_err();
+            assert!(errors.iter().any(|e| e.contains(expected)), "应报告 \"{}\"，实际为 {:?}", expected, errors);
+        }
+
+        let mut missing = sample_solutions_json();
+        for key in ["solutions", "fingerprints", "total_solutions", "difficulty", "difficulty_tier"] {
+            missing.as_object_mut().unwrap().remove(key);
+        }
+        assert_eq!(validate_solutions_json(&missing).unwrap_err().len(), 4);
+    }
+}